@@ -10,13 +10,18 @@
 //! - Admin access control
 //! - Developer self-service (register, update, deactivate)
 //! - Filtering by category, chain, and developer
+//! - Owner-managed infrastructure tier catalog (price and transaction quota)
+//! - Suspension of paid-tier apps with overdue infrastructure bills
+//! - Gasless registration and updates via EIP-712 signed requests
 
-#![cfg_attr(not(feature = "export-abi"), no_main)]
+#![cfg_attr(not(any(test, feature = "export-abi")), no_main)]
 extern crate alloc;
 
 use stylus_sdk::prelude::*;
-use alloy_primitives::{Address, U256};
-use alloy_sol_types::sol;
+use stylus_sdk::call::RawCall;
+use alloy_primitives::{Address, B256, U256};
+use alloy_sol_types::{sol, Eip712Domain, SolStruct};
+use alloc::borrow::Cow;
 use alloc::string::String;
 use alloc::vec::Vec;
 
@@ -30,6 +35,42 @@ sol! {
     event AppDeactivated(uint256 app_id);
     event AppFeatured(uint64 indexed app_id);
     event AdminAdded();
+    event TierUpdated(uint256 indexed tier_id, string name, uint256 monthly_price, uint256 tx_quota, bool is_active);
+    event TierChanged(uint256 indexed app_id, uint256 old_tier, uint256 new_tier, uint256 effective_at);
+    event BillingRecorded(uint256 app_id, uint256 period);
+    event AppSuspended(uint256 app_id, uint256 period);
+    event AppUnsuspended(uint256 app_id, uint256 period);
+    event BillerUpdated(address indexed biller, bool is_biller);
+}
+
+// EIP-712 signed requests (relayed by a third party that pays gas)
+sol! {
+    struct RegisterApp {
+        string name;
+        string description;
+        string app_url;
+        string logo_url;
+        string category;
+        uint64 chain_id;
+        bool built_with_varity;
+        string github_url;
+        string[] screenshot_urls;
+        string tier;
+        string services;
+        address developer;
+        uint256 nonce;
+        uint256 deadline;
+    }
+
+    struct UpdateApp {
+        uint64 app_id;
+        string description;
+        string app_url;
+        string[] screenshot_urls;
+        address developer;
+        uint256 nonce;
+        uint256 deadline;
+    }
 }
 
 // Custom error codes
@@ -39,6 +80,37 @@ const ERROR_APP_ALREADY_APPROVED: &[u8] = b"AppAlreadyApproved";
 const ERROR_APP_NOT_APPROVED: &[u8] = b"AppNotApproved";
 const ERROR_INVALID_INPUT: &[u8] = b"InvalidInput";
 const ERROR_INVALID_TIER: &[u8] = b"InvalidTier";
+const ERROR_BILLING_NOT_OVERDUE: &[u8] = b"BillingNotOverdue";
+const ERROR_BILLING_OVERDUE: &[u8] = b"BillingOverdue";
+const ERROR_INVALID_SIGNATURE: &[u8] = b"InvalidSignature";
+const ERROR_SIGNATURE_EXPIRED: &[u8] = b"SignatureExpired";
+const ERROR_TIERS_SEEDED: &[u8] = b"TiersAlreadySeeded";
+const ERROR_ARITHMETIC_OVERFLOW: &[u8] = b"ArithmeticOverflow";
+
+/// ecrecover precompile
+const ECRECOVER: [u8; 20] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 1
+];
+
+/// Default tier catalog: (name, monthly price in USDC, monthly tx quota)
+const DEFAULT_TIERS: [(&str, u64, u64); 4] = [
+    ("free", 0, 0),
+    ("starter", 49_000_000, 50_000),
+    ("growth", 99_000_000, 250_000),
+    ("enterprise", 199_000_000, 1_000_000),
+];
+
+/// Length of an infrastructure billing period (30 days). Period N covers
+/// [N * 30 days, (N + 1) * 30 days) since the Unix epoch.
+const BILLING_PERIOD_SECONDS: u64 = 30 * 86_400;
+/// Time after a period starts before an unpaid bill can suspend the app
+const BILLING_GRACE_SECONDS: u64 = 7 * 86_400;
+
+/// Narrow a stored value to u64 for a view, failing with ArithmeticOverflow instead of panicking
+fn to_u64(value: U256) -> Result<u64, Vec<u8>> {
+    value.try_into().map_err(|_| ERROR_ARITHMETIC_OVERFLOW.to_vec())
+}
 
 // Define storage using sol_storage! macro
 sol_storage! {
@@ -70,8 +142,10 @@ sol_storage! {
         mapping(uint256 => bool) app_built_with_varity;
         mapping(uint256 => string) app_github_urls;
         mapping(uint256 => uint256) app_screenshot_counts;
-        /// Infrastructure tier: "free", "starter", "growth", "enterprise"
+        /// Infrastructure tier name (must exist in the tier catalog)
         mapping(uint256 => string) app_tiers;
+        /// Timestamp from which the app's current tier applies
+        mapping(uint256 => uint256) app_tier_effective_at;
         /// Infrastructure services used: comma-separated (e.g., "auth,backend,storage")
         mapping(uint256 => string) app_services;
 
@@ -81,6 +155,33 @@ sol_storage! {
         // Pending and featured arrays (stored as mapping with count)
         mapping(uint256 => uint256) pending_apps;
         mapping(uint256 => uint256) featured_apps;
+
+        // Tier catalog (tier_id => field, IDs 0..tier_count)
+        uint256 tier_count;
+        mapping(uint256 => string) tier_names;
+        /// Monthly price in USDC (6 decimals)
+        mapping(uint256 => uint256) tier_monthly_prices;
+        /// Monthly transaction quota (0 = unmetered)
+        mapping(uint256 => uint256) tier_tx_quotas;
+        mapping(uint256 => bool) tier_is_active;
+
+        // Infrastructure billing (mirrored from VarityPayments on Arbitrum One)
+        /// Addresses allowed to record billing payments
+        mapping(address => bool) billers;
+        /// Paid periods (app_id => period => paid), period = timestamp / BILLING_PERIOD_SECONDS
+        mapping(uint256 => mapping(uint256 => bool)) app_paid_periods;
+        /// Suspended for an overdue bill (hidden from listings)
+        mapping(uint256 => bool) app_is_suspended;
+        /// Oldest unpaid period when the app was suspended
+        mapping(uint256 => uint256) app_suspended_period;
+        /// Timestamp from which the app owes bills: registration, or its
+        /// move from the free tier to a paid one (0 = tier effective date)
+        mapping(uint256 => uint256) app_billing_since;
+        /// Oldest period that may be unpaid, advanced as payments are recorded
+        mapping(uint256 => uint256) app_oldest_unpaid_period;
+
+        /// Signed requests: signer => next nonce (replay protection)
+        mapping(address => uint256) nonces;
     }
 }
 
@@ -101,12 +202,28 @@ impl VarityAppRegistry {
         self.next_app_id.set(U256::from(1));
         self.pending_count.set(U256::from(0));
         self.featured_count.set(U256::from(0));
+
+        self.seed_tiers();
+        Ok(())
+    }
+
+    /// Seed the default tier catalog on a registry deployed before it
+    /// existed (owner only, while the catalog is empty)
+    pub fn seed_default_tiers(&mut self) -> Result<(), Vec<u8>> {
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if self.tier_count.get() != U256::ZERO {
+            return Err(ERROR_TIERS_SEEDED.to_vec());
+        }
+
+        self.seed_tiers();
         Ok(())
     }
 
     /// Register a new app (pending approval)
     ///
-    /// Infrastructure tiers:
+    /// `tier` must name an active tier in the catalog. Default tiers:
     /// - "free": $0/mo - Testnet only
     /// - "starter": $49/mo - 50k transactions
     /// - "growth": $99/mo - 250k transactions
@@ -126,74 +243,11 @@ impl VarityAppRegistry {
         tier: String,
         services: String,
     ) -> Result<u64, Vec<u8>> {
-        // Validate inputs
-        if name.is_empty() || name.len() > 100 {
-            return Err(ERROR_INVALID_INPUT.to_vec());
-        }
-        if description.is_empty() || description.len() > 1000 {
-            return Err(ERROR_INVALID_INPUT.to_vec());
-        }
-        if app_url.is_empty() {
-            return Err(ERROR_INVALID_INPUT.to_vec());
-        }
-        if screenshot_urls.len() > 5 {
-            return Err(ERROR_INVALID_INPUT.to_vec());
-        }
-        // Validate tier - must be one of: free, starter, growth, enterprise
-        if tier != "free" && tier != "starter" && tier != "growth" && tier != "enterprise" {
-            return Err(ERROR_INVALID_TIER.to_vec());
-        }
-
-        // Get next app ID
-        let app_id_u256 = self.next_app_id.get();
-        let app_id = app_id_u256.to::<u64>();
-        self.next_app_id.set(app_id_u256 + U256::from(1));
-
-        // Store app data
-        self.app_names.setter(app_id_u256).set_str(&name);
-        self.app_descriptions.setter(app_id_u256).set_str(&description);
-        self.app_urls.setter(app_id_u256).set_str(&app_url);
-        self.app_logo_urls.setter(app_id_u256).set_str(&logo_url);
-        self.app_categories.setter(app_id_u256).set_str(&category);
-        self.app_chain_ids.setter(app_id_u256).set(U256::from(chain_id));
-        self.app_developers.setter(app_id_u256).set(self.__stylus_host.msg_sender());
-        self.app_is_active.setter(app_id_u256).set(true);
-        self.app_is_approved.setter(app_id_u256).set(false); // Pending
-        self.app_created_at.setter(app_id_u256).set(U256::from(self.__stylus_host.block_timestamp()));
-        self.app_built_with_varity.setter(app_id_u256).set(built_with_varity);
-        self.app_github_urls.setter(app_id_u256).set_str(&github_url);
-        self.app_screenshot_counts.setter(app_id_u256).set(U256::from(screenshot_urls.len() as u64));
-        self.app_tiers.setter(app_id_u256).set_str(&tier);
-        self.app_services.setter(app_id_u256).set_str(&services);
-
-        // Store screenshots
-        for (i, url) in screenshot_urls.iter().enumerate() {
-            self.app_screenshots
-                .setter(app_id_u256)
-                .setter(U256::from(i))
-                .set_str(url);
-        }
-
-        // Add to pending list
-        let pending_idx = self.pending_count.get();
-        self.pending_apps.setter(pending_idx).set(app_id_u256);
-        self.pending_count.set(pending_idx + U256::from(1));
-
-        // Emit AppSubmitted event for subgraph indexing
-        self.vm().log(AppSubmitted {
-            app_id: app_id_u256,
-            developer: self.__stylus_host.msg_sender(),
-            name: name.clone(),
-            description: description.clone(),
-            website_url: app_url.clone(),
-            github_url: github_url.clone(),
-            logo_url: logo_url.clone(),
-            category: category.clone(),
-            tier: tier.clone(),
-            services: services.clone(),
-        });
-
-        Ok(app_id)
+        let developer = self.__stylus_host.msg_sender();
+        self.create_app(
+            developer, name, description, app_url, logo_url, category, chain_id,
+            built_with_varity, github_url, screenshot_urls, tier, services,
+        )
     }
 
     /// Approve an app (admin only)
@@ -263,6 +317,93 @@ impl VarityAppRegistry {
         app_url: String,
         screenshot_urls: Vec<String>,
     ) -> Result<(), Vec<u8>> {
+        let developer = self.__stylus_host.msg_sender();
+        self.apply_app_update(developer, app_id, description, app_url, screenshot_urls)
+    }
+
+    /// Register an app for `developer` from an EIP-712 signed request
+    /// (anyone may relay)
+    ///
+    /// `developer` signs RegisterApp with the same fields as register_app()
+    /// plus their nonce and a deadline, for this contract's domain
+    /// ("VarityAppRegistry", version "1").
+    #[allow(clippy::too_many_arguments)]
+    pub fn register_app_by_sig(
+        &mut self,
+        name: String,
+        description: String,
+        app_url: String,
+        logo_url: String,
+        category: String,
+        chain_id: u64,
+        built_with_varity: bool,
+        github_url: String,
+        screenshot_urls: Vec<String>,
+        tier: String,
+        services: String,
+        developer: Address,
+        deadline: U256,
+        v: u8,
+        r: B256,
+        s: B256,
+    ) -> Result<u64, Vec<u8>> {
+        let request = RegisterApp {
+            name: name.clone(),
+            description: description.clone(),
+            app_url: app_url.clone(),
+            logo_url: logo_url.clone(),
+            category: category.clone(),
+            chain_id,
+            built_with_varity,
+            github_url: github_url.clone(),
+            screenshot_urls: screenshot_urls.clone(),
+            tier: tier.clone(),
+            services: services.clone(),
+            developer,
+            nonce: self.nonces.get(developer),
+            deadline,
+        };
+        let digest = request.eip712_signing_hash(&self.eip712_domain());
+        self.use_signature(developer, digest, deadline, v, r, s)?;
+
+        self.create_app(
+            developer, name, description, app_url, logo_url, category, chain_id,
+            built_with_varity, github_url, screenshot_urls, tier, services,
+        )
+    }
+
+    /// Update app metadata for `developer` from an EIP-712 signed request
+    /// (anyone may relay, see register_app_by_sig)
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_app_by_sig(
+        &mut self,
+        app_id: u64,
+        description: String,
+        app_url: String,
+        screenshot_urls: Vec<String>,
+        developer: Address,
+        deadline: U256,
+        v: u8,
+        r: B256,
+        s: B256,
+    ) -> Result<(), Vec<u8>> {
+        let request = UpdateApp {
+            app_id,
+            description: description.clone(),
+            app_url: app_url.clone(),
+            screenshot_urls: screenshot_urls.clone(),
+            developer,
+            nonce: self.nonces.get(developer),
+            deadline,
+        };
+        let digest = request.eip712_signing_hash(&self.eip712_domain());
+        self.use_signature(developer, digest, deadline, v, r, s)?;
+
+        self.apply_app_update(developer, app_id, description, app_url, screenshot_urls)
+    }
+
+    /// Deactivate an app (developer only)
+    pub fn deactivate_app(&mut self, app_id: u64) -> Result<(), Vec<u8>> {
         let app_id_u256 = U256::from(app_id);
 
         // Check app exists
@@ -275,58 +416,152 @@ impl VarityAppRegistry {
             return Err(ERROR_UNAUTHORIZED.to_vec());
         }
 
-        // Validate inputs
-        if description.is_empty() || description.len() > 1000 {
-            return Err(ERROR_INVALID_INPUT.to_vec());
+        // Deactivate
+        self.app_is_active.setter(app_id_u256).set(false);
+
+        // Emit event
+        self.vm().log(AppDeactivated {
+            app_id: app_id_u256,
+        });
+
+        Ok(())
+    }
+
+    /// Change an app's infrastructure tier (developer only)
+    ///
+    /// The new tier applies from the current block timestamp. TierChanged
+    /// carries catalog tier IDs, which billing mirrors with
+    /// record_tier_change() to prorate the period across the change.
+    /// Apps that are suspended or have an overdue bill must pay it first.
+    pub fn change_tier(&mut self, app_id: u64, new_tier: String) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+
+        // Check app exists
+        if self.app_developers.get(app_id_u256) == Address::ZERO {
+            return Err(ERROR_APP_NOT_FOUND.to_vec());
         }
-        if app_url.is_empty() {
-            return Err(ERROR_INVALID_INPUT.to_vec());
+
+        // Check sender is developer
+        if self.app_developers.get(app_id_u256) != self.__stylus_host.msg_sender() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
         }
-        if screenshot_urls.len() > 5 {
-            return Err(ERROR_INVALID_INPUT.to_vec());
+
+        // Validate tier against the catalog
+        let Some(new_tier_id) = self.find_active_tier(&new_tier) else {
+            return Err(ERROR_INVALID_TIER.to_vec());
+        };
+
+        let old_tier = self.app_tiers.get(app_id_u256).get_string();
+        if old_tier == new_tier {
+            return Err(ERROR_INVALID_TIER.to_vec());
         }
+        let Some(old_tier_id) = self.find_tier(&old_tier) else {
+            return Err(ERROR_INVALID_TIER.to_vec());
+        };
 
-        // Update mutable fields
-        self.app_descriptions.setter(app_id_u256).set_str(&description);
-        self.app_urls.setter(app_id_u256).set_str(&app_url);
-        self.app_screenshot_counts.setter(app_id_u256).set(U256::from(screenshot_urls.len() as u64));
+        // A tier change must not clear arrears or lift a suspension
+        let now = self.__stylus_host.block_timestamp();
+        if self.app_is_suspended.get(app_id_u256) || self.is_billing_overdue(app_id_u256, now) {
+            return Err(ERROR_BILLING_OVERDUE.to_vec());
+        }
 
-        // Update screenshots
-        for (i, url) in screenshot_urls.iter().enumerate() {
-            self.app_screenshots
-                .setter(app_id_u256)
-                .setter(U256::from(i))
-                .set_str(url);
+        let effective_at = U256::from(now);
+        // Periods spent on the free tier are never billed, unless the app
+        // left a paid tier with a period still unpaid
+        if !self.is_paid_tier(&old_tier) && !self.has_unpaid_before_free_tier(app_id_u256, now) {
+            self.app_billing_since.setter(app_id_u256).set(effective_at);
         }
+        self.app_tiers.setter(app_id_u256).set_str(&new_tier);
+        self.app_tier_effective_at.setter(app_id_u256).set(effective_at);
 
         // Emit event
-        self.vm().log(AppUpdated {
+        self.vm().log(TierChanged {
             app_id: app_id_u256,
+            old_tier: old_tier_id,
+            new_tier: new_tier_id,
+            effective_at,
         });
 
         Ok(())
     }
 
-    /// Deactivate an app (developer only)
-    pub fn deactivate_app(&mut self, app_id: u64) -> Result<(), Vec<u8>> {
+    /// Record an infrastructure bill payment for an app (billing role or admin)
+    ///
+    /// VarityPayments lives on Arbitrum One, so its BillingPayment and
+    /// InvoicePaid events are relayed here by the billing role. `period` is
+    /// the registry's period index, block timestamp / 30 days (see
+    /// get_current_billing_period). VarityPayments keys bills by an opaque
+    /// `period_hash`, so the billing role issues bills and invoices with
+    /// `period_hash` set to this index and relays it unchanged.
+    ///
+    /// Once no period is left overdue, a suspension is lifted.
+    pub fn record_billing_payment(&mut self, app_id: u64, period: u64) -> Result<(), Vec<u8>> {
+        let caller = self.__stylus_host.msg_sender();
+        if !self.billers.get(caller) && !self.admins.get(caller) {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
         let app_id_u256 = U256::from(app_id);
+        let period_u256 = U256::from(period);
 
         // Check app exists
         if self.app_developers.get(app_id_u256) == Address::ZERO {
             return Err(ERROR_APP_NOT_FOUND.to_vec());
         }
 
-        // Check sender is developer
-        if self.app_developers.get(app_id_u256) != self.__stylus_host.msg_sender() {
-            return Err(ERROR_UNAUTHORIZED.to_vec());
+        self.app_paid_periods.setter(app_id_u256).setter(period_u256).set(true);
+        let now = self.__stylus_host.block_timestamp();
+        let oldest_unpaid = self.oldest_unpaid_period(app_id_u256, now);
+        self.app_oldest_unpaid_period.setter(app_id_u256).set(U256::from(oldest_unpaid));
+
+        // Emit event
+        self.vm().log(BillingRecorded {
+            app_id: app_id_u256,
+            period: period_u256,
+        });
+
+        // Lift the suspension once the arrears are paid
+        if self.app_is_suspended.get(app_id_u256) && !self.is_billing_overdue(app_id_u256, now) {
+            self.app_is_suspended.setter(app_id_u256).set(false);
+            self.vm().log(AppUnsuspended {
+                app_id: app_id_u256,
+                period: period_u256,
+            });
         }
 
-        // Deactivate
-        self.app_is_active.setter(app_id_u256).set(false);
+        Ok(())
+    }
+
+    /// Suspend an app whose current infrastructure bill is overdue (anyone)
+    ///
+    /// An app on a paid tier is overdue when its oldest unpaid period, up to
+    /// and including the current one, is past the grace window. Grace runs
+    /// from the later of the period start and the start of billing, so a
+    /// missed period stays overdue after the next one begins.
+    pub fn enforce_billing(&mut self, app_id: u64) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+
+        // Check app exists
+        if self.app_developers.get(app_id_u256) == Address::ZERO {
+            return Err(ERROR_APP_NOT_FOUND.to_vec());
+        }
+        if self.app_is_suspended.get(app_id_u256) {
+            return Err(ERROR_BILLING_NOT_OVERDUE.to_vec());
+        }
+
+        let now = self.__stylus_host.block_timestamp();
+        if !self.is_billing_overdue(app_id_u256, now) {
+            return Err(ERROR_BILLING_NOT_OVERDUE.to_vec());
+        }
+        let period = self.oldest_unpaid_period(app_id_u256, now);
+
+        self.app_is_suspended.setter(app_id_u256).set(true);
+        self.app_suspended_period.setter(app_id_u256).set(U256::from(period));
 
         // Emit event
-        self.vm().log(AppDeactivated {
+        self.vm().log(AppSuspended {
             app_id: app_id_u256,
+            period: U256::from(period),
         });
 
         Ok(())
@@ -379,6 +614,79 @@ impl VarityAppRegistry {
         Ok(())
     }
 
+    /// Grant or revoke the billing role (owner only)
+    pub fn set_biller(&mut self, biller: Address, is_biller: bool) -> Result<(), Vec<u8>> {
+        // Only owner can manage billers
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        self.billers.setter(biller).set(is_biller);
+
+        // Emit event
+        self.vm().log(BillerUpdated {
+            biller,
+            is_biller,
+        });
+
+        Ok(())
+    }
+
+    /// Add or update a tier in the catalog (owner only)
+    ///
+    /// `tier_id` must be an existing ID or `tier_count` (to append a new tier).
+    /// Apps store their tier by name, so an existing tier keeps its name.
+    /// Deactivating a tier prevents new registrations and changes to it;
+    /// apps already on the tier keep it. Contracts initialized before the
+    /// catalog existed start empty and are seeded by the owner through here.
+    pub fn set_tier(
+        &mut self,
+        tier_id: u64,
+        name: String,
+        monthly_price: U256,
+        tx_quota: u64,
+        is_active: bool,
+    ) -> Result<(), Vec<u8>> {
+        // Only owner can manage tiers
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        let tier_id_u256 = U256::from(tier_id);
+        let tier_count = self.tier_count.get();
+        if tier_id_u256 > tier_count {
+            return Err(ERROR_INVALID_TIER.to_vec());
+        }
+        if name.is_empty() || name.len() > 32 {
+            return Err(ERROR_INVALID_INPUT.to_vec());
+        }
+
+        // Names are set once, on append, and stay unique so apps can resolve them
+        if tier_id_u256 < tier_count {
+            if self.tier_names.get(tier_id_u256).get_string() != name {
+                return Err(ERROR_INVALID_TIER.to_vec());
+            }
+        } else if self.find_tier(&name).is_some() {
+            return Err(ERROR_INVALID_TIER.to_vec());
+        }
+
+        self.store_tier(tier_id_u256, &name, monthly_price, U256::from(tx_quota), is_active);
+        if tier_id_u256 == tier_count {
+            self.tier_count.set(tier_count + U256::from(1));
+        }
+
+        // Emit event
+        self.vm().log(TierUpdated {
+            tier_id: tier_id_u256,
+            name,
+            monthly_price,
+            tx_quota: U256::from(tx_quota),
+            is_active,
+        });
+
+        Ok(())
+    }
+
     /// Get app by ID
     #[allow(clippy::type_complexity)]
     pub fn get_app(&self, app_id: u64) -> Result<(
@@ -448,6 +756,43 @@ impl VarityAppRegistry {
         Ok(self.app_tiers.get(app_id_u256).get_string())
     }
 
+    /// Get the timestamp from which an app's current tier applies
+    pub fn get_app_tier_effective_at(&self, app_id: u64) -> Result<u64, Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+
+        // Check app exists
+        if self.app_developers.get(app_id_u256) == Address::ZERO {
+            return Err(ERROR_APP_NOT_FOUND.to_vec());
+        }
+
+        Ok(self.app_tier_effective_at.get(app_id_u256).to::<u64>())
+    }
+
+    /// Get a tier from the catalog
+    pub fn get_tier(&self, tier_id: u64) -> Result<(
+        String,  // name
+        U256,    // monthly_price
+        u64,     // tx_quota
+        bool,    // is_active
+    ), Vec<u8>> {
+        let tier_id_u256 = U256::from(tier_id);
+        if tier_id_u256 >= self.tier_count.get() {
+            return Err(ERROR_INVALID_TIER.to_vec());
+        }
+
+        Ok((
+            self.tier_names.get(tier_id_u256).get_string(),
+            self.tier_monthly_prices.get(tier_id_u256),
+            to_u64(self.tier_tx_quotas.get(tier_id_u256))?,
+            self.tier_is_active.get(tier_id_u256),
+        ))
+    }
+
+    /// Get number of tiers in the catalog
+    pub fn get_tier_count(&self) -> Result<u64, Vec<u8>> {
+        to_u64(self.tier_count.get())
+    }
+
     /// Get screenshot URL by index
     pub fn get_app_screenshot(&self, app_id: u64, index: u64) -> Result<String, Vec<u8>> {
         let app_id_u256 = U256::from(app_id);
@@ -490,12 +835,17 @@ impl VarityAppRegistry {
     /// Get featured apps
     pub fn get_featured_apps(&self, max_results: u64) -> Result<Vec<u64>, Vec<u8>> {
         let featured_count = self.featured_count.get().to::<u64>();
-        let limit = if max_results < featured_count { max_results } else { featured_count };
-
         let mut result = Vec::new();
-        for i in 0..limit {
-            let app_id = self.featured_apps.get(U256::from(i)).to::<u64>();
-            result.push(app_id);
+
+        for i in 0..featured_count {
+            if result.len() >= max_results as usize {
+                break;
+            }
+
+            let app_id_u256 = self.featured_apps.get(U256::from(i));
+            if !self.app_is_suspended.get(app_id_u256) {
+                result.push(app_id_u256.to::<u64>());
+            }
         }
 
         Ok(result)
@@ -520,6 +870,44 @@ impl VarityAppRegistry {
         Ok(result)
     }
 
+    /// Check if an app is suspended for an overdue bill
+    pub fn is_app_suspended(&self, app_id: u64) -> Result<bool, Vec<u8>> {
+        Ok(self.app_is_suspended.get(U256::from(app_id)))
+    }
+
+    /// Check if an app's bill for a billing period has been recorded
+    pub fn is_period_paid(&self, app_id: u64, period: u64) -> Result<bool, Vec<u8>> {
+        Ok(self.app_paid_periods.getter(U256::from(app_id)).get(U256::from(period)))
+    }
+
+    /// Check if an app could be suspended by enforce_billing() right now
+    pub fn is_billing_overdue_now(&self, app_id: u64) -> Result<bool, Vec<u8>> {
+        let now = self.__stylus_host.block_timestamp();
+        Ok(!self.app_is_suspended.get(U256::from(app_id))
+            && self.is_billing_overdue(U256::from(app_id), now))
+    }
+
+    /// Get an app's oldest billed period without a recorded payment (the
+    /// period after the current one when all are paid)
+    pub fn get_oldest_unpaid_period(&self, app_id: u64) -> Result<u64, Vec<u8>> {
+        Ok(self.oldest_unpaid_period(U256::from(app_id), self.__stylus_host.block_timestamp()))
+    }
+
+    /// Get the current billing period index
+    pub fn get_current_billing_period(&self) -> Result<u64, Vec<u8>> {
+        Ok(self.__stylus_host.block_timestamp() / BILLING_PERIOD_SECONDS)
+    }
+
+    /// Get the next nonce for an account's signed requests
+    pub fn get_nonce(&self, account: Address) -> Result<U256, Vec<u8>> {
+        Ok(self.nonces.get(account))
+    }
+
+    /// Check if an address has the billing role
+    pub fn is_biller(&self, address: Address) -> Result<bool, Vec<u8>> {
+        Ok(self.billers.get(address))
+    }
+
     /// Get approved and active apps by category
     pub fn get_apps_by_category(&self, category: String, max_results: u64) -> Result<Vec<u64>, Vec<u8>> {
         let next_id = self.next_app_id.get().to::<u64>();
@@ -532,7 +920,8 @@ impl VarityAppRegistry {
 
             let app_id_u256 = U256::from(i);
             if self.app_is_active.get(app_id_u256)
-                && self.app_is_approved.get(app_id_u256) {
+                && self.app_is_approved.get(app_id_u256)
+                && !self.app_is_suspended.get(app_id_u256) {
                 let app_category = self.app_categories.get(app_id_u256).get_string();
                 if app_category == category {
                     result.push(i);
@@ -556,6 +945,7 @@ impl VarityAppRegistry {
             let app_id_u256 = U256::from(i);
             if self.app_is_active.get(app_id_u256)
                 && self.app_is_approved.get(app_id_u256)
+                && !self.app_is_suspended.get(app_id_u256)
                 && self.app_chain_ids.get(app_id_u256).to::<u64>() == chain_id {
                 result.push(i);
             }
@@ -564,7 +954,7 @@ impl VarityAppRegistry {
         Ok(result)
     }
 
-    /// Get all approved and active apps
+    /// Get all approved, active and unsuspended apps
     pub fn get_all_apps(&self, max_results: u64) -> Result<Vec<u64>, Vec<u8>> {
         let next_id = self.next_app_id.get().to::<u64>();
         let mut result = Vec::new();
@@ -575,7 +965,9 @@ impl VarityAppRegistry {
             }
 
             let app_id_u256 = U256::from(i);
-            if self.app_is_active.get(app_id_u256) && self.app_is_approved.get(app_id_u256) {
+            if self.app_is_active.get(app_id_u256)
+                && self.app_is_approved.get(app_id_u256)
+                && !self.app_is_suspended.get(app_id_u256) {
                 result.push(i);
             }
         }
@@ -600,9 +992,337 @@ impl VarityAppRegistry {
     }
 }
 
+// Internal helpers (not exposed in the ABI)
+impl VarityAppRegistry {
+    /// Write the default tiers as IDs 0..4, emitting TierUpdated for each
+    fn seed_tiers(&mut self) {
+        for (tier_id, (name, monthly_price, tx_quota)) in DEFAULT_TIERS.iter().enumerate() {
+            self.store_tier(U256::from(tier_id), name, U256::from(*monthly_price), U256::from(*tx_quota), true);
+            self.vm().log(TierUpdated {
+                tier_id: U256::from(tier_id),
+                name: name.to_string(),
+                monthly_price: U256::from(*monthly_price),
+                tx_quota: U256::from(*tx_quota),
+                is_active: true,
+            });
+        }
+        self.tier_count.set(U256::from(DEFAULT_TIERS.len()));
+    }
+
+    /// Write a tier's fields to the catalog
+    fn store_tier(&mut self, tier_id: U256, name: &str, monthly_price: U256, tx_quota: U256, is_active: bool) {
+        self.tier_names.setter(tier_id).set_str(name);
+        self.tier_monthly_prices.setter(tier_id).set(monthly_price);
+        self.tier_tx_quotas.setter(tier_id).set(tx_quota);
+        self.tier_is_active.setter(tier_id).set(is_active);
+    }
+
+    /// Look up a tier ID by name
+    fn find_tier(&self, name: &str) -> Option<U256> {
+        let tier_count = self.tier_count.get().to::<u64>();
+        (0..tier_count)
+            .map(U256::from)
+            .find(|id| self.tier_names.get(*id).get_string() == name)
+    }
+
+    /// Look up an active tier ID by name
+    fn find_active_tier(&self, name: &str) -> Option<U256> {
+        self.find_tier(name).filter(|id| self.tier_is_active.get(*id))
+    }
+
+    /// Store a new pending app for `developer`
+    #[allow(clippy::too_many_arguments)]
+    fn create_app(
+        &mut self,
+        developer: Address,
+        name: String,
+        description: String,
+        app_url: String,
+        logo_url: String,
+        category: String,
+        chain_id: u64,
+        built_with_varity: bool,
+        github_url: String,
+        screenshot_urls: Vec<String>,
+        tier: String,
+        services: String,
+    ) -> Result<u64, Vec<u8>> {
+        // Validate inputs
+        if name.is_empty() || name.len() > 100 {
+            return Err(ERROR_INVALID_INPUT.to_vec());
+        }
+        if description.is_empty() || description.len() > 1000 {
+            return Err(ERROR_INVALID_INPUT.to_vec());
+        }
+        if app_url.is_empty() {
+            return Err(ERROR_INVALID_INPUT.to_vec());
+        }
+        if screenshot_urls.len() > 5 {
+            return Err(ERROR_INVALID_INPUT.to_vec());
+        }
+        // Validate tier against the catalog
+        if self.find_active_tier(&tier).is_none() {
+            return Err(ERROR_INVALID_TIER.to_vec());
+        }
+
+        // Get next app ID
+        let app_id_u256 = self.next_app_id.get();
+        let app_id = app_id_u256.to::<u64>();
+        self.next_app_id.set(app_id_u256 + U256::from(1));
+
+        // Store app data
+        self.app_names.setter(app_id_u256).set_str(&name);
+        self.app_descriptions.setter(app_id_u256).set_str(&description);
+        self.app_urls.setter(app_id_u256).set_str(&app_url);
+        self.app_logo_urls.setter(app_id_u256).set_str(&logo_url);
+        self.app_categories.setter(app_id_u256).set_str(&category);
+        self.app_chain_ids.setter(app_id_u256).set(U256::from(chain_id));
+        self.app_developers.setter(app_id_u256).set(developer);
+        self.app_is_active.setter(app_id_u256).set(true);
+        self.app_is_approved.setter(app_id_u256).set(false); // Pending
+        self.app_created_at.setter(app_id_u256).set(U256::from(self.__stylus_host.block_timestamp()));
+        self.app_built_with_varity.setter(app_id_u256).set(built_with_varity);
+        self.app_github_urls.setter(app_id_u256).set_str(&github_url);
+        self.app_screenshot_counts.setter(app_id_u256).set(U256::from(screenshot_urls.len() as u64));
+        self.app_tiers.setter(app_id_u256).set_str(&tier);
+        self.app_tier_effective_at.setter(app_id_u256).set(U256::from(self.__stylus_host.block_timestamp()));
+        self.app_billing_since.setter(app_id_u256).set(U256::from(self.__stylus_host.block_timestamp()));
+        self.app_services.setter(app_id_u256).set_str(&services);
+
+        // Store screenshots
+        for (i, url) in screenshot_urls.iter().enumerate() {
+            self.app_screenshots
+                .setter(app_id_u256)
+                .setter(U256::from(i))
+                .set_str(url);
+        }
+
+        // Add to pending list
+        let pending_idx = self.pending_count.get();
+        self.pending_apps.setter(pending_idx).set(app_id_u256);
+        self.pending_count.set(pending_idx + U256::from(1));
+
+        // Emit AppSubmitted event for subgraph indexing
+        self.vm().log(AppSubmitted {
+            app_id: app_id_u256,
+            developer,
+            name: name.clone(),
+            description: description.clone(),
+            website_url: app_url.clone(),
+            github_url: github_url.clone(),
+            logo_url: logo_url.clone(),
+            category: category.clone(),
+            tier: tier.clone(),
+            services: services.clone(),
+        });
+
+        Ok(app_id)
+    }
+
+    /// Update an app's mutable metadata on behalf of `developer`
+    fn apply_app_update(
+        &mut self,
+        developer: Address,
+        app_id: u64,
+        description: String,
+        app_url: String,
+        screenshot_urls: Vec<String>,
+    ) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+
+        // Check app exists
+        if self.app_developers.get(app_id_u256) == Address::ZERO {
+            return Err(ERROR_APP_NOT_FOUND.to_vec());
+        }
+
+        // Check sender is developer
+        if self.app_developers.get(app_id_u256) != developer {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        // Validate inputs
+        if description.is_empty() || description.len() > 1000 {
+            return Err(ERROR_INVALID_INPUT.to_vec());
+        }
+        if app_url.is_empty() {
+            return Err(ERROR_INVALID_INPUT.to_vec());
+        }
+        if screenshot_urls.len() > 5 {
+            return Err(ERROR_INVALID_INPUT.to_vec());
+        }
+
+        // Update mutable fields
+        self.app_descriptions.setter(app_id_u256).set_str(&description);
+        self.app_urls.setter(app_id_u256).set_str(&app_url);
+        self.app_screenshot_counts.setter(app_id_u256).set(U256::from(screenshot_urls.len() as u64));
+
+        // Update screenshots
+        for (i, url) in screenshot_urls.iter().enumerate() {
+            self.app_screenshots
+                .setter(app_id_u256)
+                .setter(U256::from(i))
+                .set_str(url);
+        }
+
+        // Emit event
+        self.vm().log(AppUpdated {
+            app_id: app_id_u256,
+        });
+
+        Ok(())
+    }
+
+    /// EIP-712 domain for signed requests
+    fn eip712_domain(&self) -> Eip712Domain {
+        Eip712Domain::new(
+            Some(Cow::Borrowed("VarityAppRegistry")),
+            Some(Cow::Borrowed("1")),
+            Some(U256::from(self.vm().chain_id())),
+            Some(self.vm().contract_address()),
+            None,
+        )
+    }
+
+    /// Check a signed request from `signer` and consume their nonce
+    fn use_signature(
+        &mut self,
+        signer: Address,
+        digest: B256,
+        deadline: U256,
+        v: u8,
+        r: B256,
+        s: B256,
+    ) -> Result<(), Vec<u8>> {
+        if U256::from(self.__stylus_host.block_timestamp()) > deadline {
+            return Err(ERROR_SIGNATURE_EXPIRED.to_vec());
+        }
+        if signer == Address::ZERO || self.recover_signer(digest, v, r, s)? != signer {
+            return Err(ERROR_INVALID_SIGNATURE.to_vec());
+        }
+
+        let nonce = self.nonces.get(signer);
+        let next_nonce = nonce.checked_add(U256::from(1)).ok_or_else(|| ERROR_ARITHMETIC_OVERFLOW.to_vec())?;
+        self.nonces.setter(signer).set(next_nonce);
+
+        Ok(())
+    }
+
+    /// Recover the signer of a digest with the ecrecover precompile
+    fn recover_signer(&self, digest: B256, v: u8, r: B256, s: B256) -> Result<Address, Vec<u8>> {
+        let mut input = Vec::with_capacity(128);
+        input.extend_from_slice(digest.as_slice());
+        input.extend_from_slice(&U256::from(v).to_be_bytes::<32>());
+        input.extend_from_slice(r.as_slice());
+        input.extend_from_slice(s.as_slice());
+
+        let output = unsafe {
+            RawCall::new_static(&self.__stylus_host)
+                .call(Address::from_slice(&ECRECOVER), &input)
+                .map_err(|_| ERROR_INVALID_SIGNATURE.to_vec())?
+        };
+
+        // Invalid signatures return no data
+        if output.len() != 32 {
+            return Err(ERROR_INVALID_SIGNATURE.to_vec());
+        }
+        let signer = Address::from_slice(&output[12..]);
+        if signer == Address::ZERO {
+            return Err(ERROR_INVALID_SIGNATURE.to_vec());
+        }
+
+        Ok(signer)
+    }
+
+    /// Whether `tier` is in the catalog with a nonzero monthly price
+    fn is_paid_tier(&self, tier: &str) -> bool {
+        self.find_tier(tier)
+            .is_some_and(|id| self.tier_monthly_prices.get(id) > U256::ZERO)
+    }
+
+    /// Timestamp from which an app owes bills. Apps registered before this
+    /// was tracked owe from their tier effective date.
+    fn billing_since(&self, app_id: U256) -> u64 {
+        let since = self.app_billing_since.get(app_id);
+        if since == U256::ZERO {
+            return self.app_tier_effective_at.get(app_id).saturating_to::<u64>();
+        }
+        since.saturating_to::<u64>()
+    }
+
+    /// Oldest billed period without a recorded payment. Returns the period
+    /// after the current one when every billed period is paid.
+    fn oldest_unpaid_period(&self, app_id: U256, now: u64) -> u64 {
+        let current = now / BILLING_PERIOD_SECONDS;
+        let mut period = self
+            .app_oldest_unpaid_period
+            .get(app_id)
+            .saturating_to::<u64>()
+            .max(self.billing_since(app_id) / BILLING_PERIOD_SECONDS);
+        while period <= current && self.app_paid_periods.getter(app_id).get(U256::from(period)) {
+            period += 1;
+        }
+        period
+    }
+
+    /// Whether an app now on the free tier moved there from a paid tier with
+    /// a billed period (up to the one it moved in) still unpaid
+    fn has_unpaid_before_free_tier(&self, app_id: U256, now: u64) -> bool {
+        let free_since = self.app_tier_effective_at.get(app_id).saturating_to::<u64>();
+        self.billing_since(app_id) < free_since
+            && self.oldest_unpaid_period(app_id, now) <= free_since / BILLING_PERIOD_SECONDS
+    }
+
+    /// Whether an app on a paid tier has an unpaid period past its grace window
+    fn is_billing_overdue(&self, app_id: U256, now: u64) -> bool {
+        let tier = self.app_tiers.get(app_id).get_string();
+        if !self.is_paid_tier(&tier) {
+            return false;
+        }
+        let period = self.oldest_unpaid_period(app_id, now);
+        if period > now / BILLING_PERIOD_SECONDS {
+            return false;
+        }
+
+        // Grace runs from the period start, or from the start of billing if later
+        let period_start = period * BILLING_PERIOD_SECONDS;
+        now >= period_start.max(self.billing_since(app_id)) + BILLING_GRACE_SECONDS
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use stylus_sdk::testing::*;
+
+    fn mock_address(seed: u8) -> Address {
+        Address::from([seed; 20])
+    }
+
+    /// Deploy and initialize the contract with `owner` as owner and first admin
+    fn setup(vm: &TestVM, owner: Address) -> VarityAppRegistry {
+        let mut contract = VarityAppRegistry::from(vm);
+        vm.set_sender(owner);
+        contract.initialize().unwrap();
+        contract
+    }
+
+    /// Register a minimal app on `tier` as `developer`
+    fn register(vm: &TestVM, contract: &mut VarityAppRegistry, developer: Address, tier: &str) -> Result<u64, Vec<u8>> {
+        vm.set_sender(developer);
+        contract.register_app(
+            "App".into(), "An app".into(), "https://app.example".into(), String::new(),
+            "tools".into(), 33529, true, String::new(), Vec::new(), tier.into(), String::new(),
+        )
+    }
+
+    /// Mock ecrecover to return `signer` for a digest signed with (v, r, s)
+    fn mock_ecrecover(vm: &TestVM, digest: B256, v: u8, r: B256, s: B256, signer: Address) {
+        let mut input = digest.to_vec();
+        input.extend_from_slice(&U256::from(v).to_be_bytes::<32>());
+        input.extend_from_slice(r.as_slice());
+        input.extend_from_slice(s.as_slice());
+        vm.mock_static_call(Address::from_slice(&ECRECOVER), input, Ok(signer.into_word().to_vec()));
+    }
 
     // Note: These are documentation tests showing the expected behavior.
     // For actual Stylus contract testing, you would use the Stylus test framework
@@ -906,6 +1626,254 @@ mod tests {
         // 5. featured_count should be 3
     }
 
+    #[test]
+    fn test_register_app_validates_tier_catalog() {
+        let vm = TestVM::default();
+        let owner = mock_address(1);
+        let developer = mock_address(2);
+        let mut contract = setup(&vm, owner);
+        assert_eq!(contract.get_tier_count().unwrap(), 4);
+        // initialize() announces the seeded catalog like seed_default_tiers()
+        assert_eq!(vm.get_emitted_logs().len(), 4);
+        assert_eq!(contract.seed_default_tiers(), Err(ERROR_TIERS_SEEDED.to_vec()));
+
+        assert_eq!(register(&vm, &mut contract, developer, "platinum"), Err(ERROR_INVALID_TIER.to_vec()));
+        assert_eq!(
+            contract.set_tier(4, "platinum".into(), U256::from(499_000_000u64), 5_000_000, true),
+            Err(ERROR_UNAUTHORIZED.to_vec())
+        );
+        assert_eq!(contract.seed_default_tiers(), Err(ERROR_UNAUTHORIZED.to_vec()));
+
+        vm.set_sender(owner);
+        contract.set_tier(4, "platinum".into(), U256::from(499_000_000u64), 5_000_000, true).unwrap();
+        assert_eq!(
+            contract.set_tier(5, "growth".into(), U256::ZERO, 0, true),
+            Err(ERROR_INVALID_TIER.to_vec())
+        );
+        // Renaming would strand apps registered under the old name
+        assert_eq!(
+            contract.set_tier(1, "basic".into(), U256::from(49_000_000u64), 50_000, true),
+            Err(ERROR_INVALID_TIER.to_vec())
+        );
+        assert_eq!(
+            contract.set_tier(5, "x".repeat(33), U256::ZERO, 0, true),
+            Err(ERROR_INVALID_INPUT.to_vec())
+        );
+        assert!(register(&vm, &mut contract, developer, "platinum").is_ok());
+
+        vm.set_sender(owner);
+        contract.set_tier(4, "platinum".into(), U256::from(499_000_000u64), 5_000_000, false).unwrap();
+        assert_eq!(register(&vm, &mut contract, developer, "platinum"), Err(ERROR_INVALID_TIER.to_vec()));
+    }
+
+    #[test]
+    fn test_tier_views_reject_oversized_values() {
+        let vm = TestVM::default();
+        let mut contract = setup(&vm, mock_address(1));
+
+        contract.tier_tx_quotas.setter(U256::from(1)).set(U256::from(u64::MAX) + U256::from(1));
+        assert_eq!(contract.get_tier(1), Err(ERROR_ARITHMETIC_OVERFLOW.to_vec()));
+        assert_eq!(contract.get_tier(0).unwrap().2, 0);
+
+        contract.tier_count.set(U256::MAX);
+        assert_eq!(contract.get_tier_count(), Err(ERROR_ARITHMETIC_OVERFLOW.to_vec()));
+    }
+
+    #[test]
+    fn test_change_tier_records_effective_date() {
+        let vm = TestVM::default();
+        let owner = mock_address(1);
+        let developer = mock_address(2);
+        let mut contract = setup(&vm, owner);
+        let app_id = register(&vm, &mut contract, developer, "starter").unwrap();
+
+        vm.set_block_timestamp(5_000);
+        vm.set_sender(owner);
+        assert_eq!(contract.change_tier(app_id, "growth".into()), Err(ERROR_UNAUTHORIZED.to_vec()));
+        vm.set_sender(developer);
+        contract.change_tier(app_id, "growth".into()).unwrap();
+        assert_eq!(contract.get_app_tier(app_id).unwrap(), "growth");
+        assert_eq!(contract.get_app_tier_effective_at(app_id).unwrap(), 5_000);
+
+        assert_eq!(contract.change_tier(app_id, "growth".into()), Err(ERROR_INVALID_TIER.to_vec()));
+        assert_eq!(contract.change_tier(app_id, "platinum".into()), Err(ERROR_INVALID_TIER.to_vec()));
+    }
+
+    #[test]
+    fn test_enforce_billing_suspends_and_payment_lifts() {
+        let vm = TestVM::default();
+        let owner = mock_address(1);
+        let developer = mock_address(2);
+        let mut contract = setup(&vm, owner);
+        let day = 86_400;
+        let registered_at = 10 * BILLING_PERIOD_SECONDS + day;
+
+        vm.set_block_timestamp(registered_at);
+        let app_id = register(&vm, &mut contract, developer, "starter").unwrap();
+        let free_app = register(&vm, &mut contract, developer, "free").unwrap();
+        vm.set_sender(owner);
+        contract.approve_app(app_id).unwrap();
+        contract.feature_app(app_id).unwrap();
+
+        // Within the grace window
+        vm.set_block_timestamp(registered_at + day);
+        assert_eq!(contract.enforce_billing(app_id), Err(ERROR_BILLING_NOT_OVERDUE.to_vec()));
+
+        vm.set_block_timestamp(registered_at + 8 * day);
+        assert_eq!(contract.enforce_billing(free_app), Err(ERROR_BILLING_NOT_OVERDUE.to_vec()));
+        vm.set_sender(developer);
+        contract.enforce_billing(app_id).unwrap();
+        assert!(contract.is_app_suspended(app_id).unwrap());
+        assert!(!contract.get_all_apps(10).unwrap().contains(&app_id));
+        assert!(contract.get_featured_apps(10).unwrap().is_empty());
+
+        // Paying the period lifts the suspension
+        vm.set_sender(owner);
+        contract.record_billing_payment(app_id, 10).unwrap();
+        assert!(!contract.is_app_suspended(app_id).unwrap());
+        assert!(contract.get_all_apps(10).unwrap().contains(&app_id));
+        assert_eq!(contract.get_featured_apps(10).unwrap(), vec![app_id]);
+        assert_eq!(contract.get_oldest_unpaid_period(app_id).unwrap(), 11);
+
+        // Period 11 was missed; period 12 is still in grace, but the app is overdue
+        vm.set_block_timestamp(12 * BILLING_PERIOD_SECONDS + day);
+        assert!(contract.is_billing_overdue_now(app_id).unwrap());
+        contract.enforce_billing(app_id).unwrap();
+        assert!(contract.is_app_suspended(app_id).unwrap());
+
+        // Paying only the current period leaves the arrears
+        contract.record_billing_payment(app_id, 12).unwrap();
+        assert!(contract.is_app_suspended(app_id).unwrap());
+        contract.record_billing_payment(app_id, 11).unwrap();
+        assert!(!contract.is_app_suspended(app_id).unwrap());
+        assert_eq!(contract.get_oldest_unpaid_period(app_id).unwrap(), 13);
+    }
+
+    #[test]
+    fn test_tier_flap_keeps_unpaid_periods() {
+        let vm = TestVM::default();
+        let owner = mock_address(1);
+        let developer = mock_address(2);
+        let mut contract = setup(&vm, owner);
+        let day = 86_400;
+        let registered_at = 10 * BILLING_PERIOD_SECONDS + day;
+
+        vm.set_block_timestamp(registered_at);
+        let app_id = register(&vm, &mut contract, developer, "starter").unwrap();
+
+        // Paid -> free -> paid within the grace window keeps period 10 billed
+        vm.set_block_timestamp(registered_at + 2 * day);
+        contract.change_tier(app_id, "free".into()).unwrap();
+        vm.set_block_timestamp(registered_at + 3 * day);
+        contract.change_tier(app_id, "starter".into()).unwrap();
+        vm.set_block_timestamp(registered_at + 8 * day);
+        assert!(contract.is_billing_overdue_now(app_id).unwrap());
+
+        // An overdue app cannot flap to the free tier and back
+        assert_eq!(contract.change_tier(app_id, "free".into()), Err(ERROR_BILLING_OVERDUE.to_vec()));
+        assert!(contract.is_billing_overdue_now(app_id).unwrap());
+        assert_eq!(contract.get_app_tier(app_id).unwrap(), "starter");
+
+        // Nor can a suspended one
+        contract.enforce_billing(app_id).unwrap();
+        assert_eq!(contract.change_tier(app_id, "free".into()), Err(ERROR_BILLING_OVERDUE.to_vec()));
+        assert!(contract.is_app_suspended(app_id).unwrap());
+
+        // Once paid, moving to the free tier and back restarts billing
+        vm.set_sender(owner);
+        contract.record_billing_payment(app_id, 10).unwrap();
+        vm.set_sender(developer);
+        contract.change_tier(app_id, "free".into()).unwrap();
+        vm.set_block_timestamp(12 * BILLING_PERIOD_SECONDS + day);
+        contract.change_tier(app_id, "starter".into()).unwrap();
+        assert_eq!(contract.get_oldest_unpaid_period(app_id).unwrap(), 12);
+        assert!(!contract.is_billing_overdue_now(app_id).unwrap());
+    }
+
+    #[test]
+    fn test_signed_requests_resolve_developer() {
+        let vm = TestVM::default();
+        let owner = mock_address(1);
+        let developer = mock_address(2);
+        let relayer = mock_address(9);
+        let mut contract = setup(&vm, owner);
+        let deadline = U256::from(1_000u64);
+        let (v, r, s) = (27u8, B256::repeat_byte(0x11), B256::repeat_byte(0x22));
+
+        // Developer signs a registration; the relayer submits it
+        let request = RegisterApp {
+            name: "App".into(),
+            description: "An app".into(),
+            app_url: "https://app.example".into(),
+            logo_url: String::new(),
+            category: "tools".into(),
+            chain_id: 33529,
+            built_with_varity: true,
+            github_url: String::new(),
+            screenshot_urls: Vec::new(),
+            tier: "starter".into(),
+            services: String::new(),
+            developer,
+            nonce: U256::ZERO,
+            deadline,
+        };
+        let digest = request.eip712_signing_hash(&contract.eip712_domain());
+        mock_ecrecover(&vm, digest, v, r, s, developer);
+        vm.set_sender(relayer);
+        let register_by_sig = |contract: &mut VarityAppRegistry| contract.register_app_by_sig(
+            request.name.clone(), request.description.clone(), request.app_url.clone(),
+            request.logo_url.clone(), request.category.clone(), request.chain_id,
+            request.built_with_varity, request.github_url.clone(), request.screenshot_urls.clone(),
+            request.tier.clone(), request.services.clone(), developer, deadline, v, r, s,
+        );
+        let app_id = register_by_sig(&mut contract).unwrap();
+        assert_eq!(contract.get_app(app_id).unwrap().7, developer);
+        assert_eq!(contract.get_apps_by_developer(relayer, 10).unwrap(), Vec::<u64>::new());
+        assert_eq!(contract.get_nonce(developer).unwrap(), U256::from(1));
+
+        // The consumed nonce changes the digest, so a replay does not recover the developer
+        let replay = RegisterApp { nonce: U256::from(1), ..request.clone() };
+        mock_ecrecover(&vm, replay.eip712_signing_hash(&contract.eip712_domain()), v, r, s, relayer);
+        assert_eq!(register_by_sig(&mut contract), Err(ERROR_INVALID_SIGNATURE.to_vec()));
+        assert_eq!(contract.get_total_apps().unwrap(), 1);
+
+        // Signed update with the next nonce
+        let update = UpdateApp {
+            app_id,
+            description: "Updated".into(),
+            app_url: "https://new.example".into(),
+            screenshot_urls: Vec::new(),
+            developer,
+            nonce: U256::from(1),
+            deadline,
+        };
+        let digest = update.eip712_signing_hash(&contract.eip712_domain());
+        mock_ecrecover(&vm, digest, v, r, s, developer);
+        contract.update_app_by_sig(
+            app_id, update.description.clone(), update.app_url.clone(), Vec::new(), developer, deadline, v, r, s,
+        ).unwrap();
+        assert_eq!(contract.get_app(app_id).unwrap().2, "Updated");
+        assert_eq!(contract.get_nonce(developer).unwrap(), U256::from(2));
+
+        // A signature for someone else's app is rejected
+        let stranger = mock_address(3);
+        let hijack = UpdateApp { developer: stranger, nonce: U256::ZERO, ..update };
+        let digest = hijack.eip712_signing_hash(&contract.eip712_domain());
+        mock_ecrecover(&vm, digest, v, r, s, stranger);
+        assert_eq!(
+            contract.update_app_by_sig(app_id, "Hijacked".into(), "https://x.example".into(), Vec::new(), stranger, deadline, v, r, s),
+            Err(ERROR_UNAUTHORIZED.to_vec())
+        );
+
+        // Past the deadline
+        vm.set_block_timestamp(2_000);
+        assert_eq!(
+            contract.update_app_by_sig(app_id, "Late".into(), "https://app.example".into(), Vec::new(), developer, deadline, v, r, s),
+            Err(ERROR_SIGNATURE_EXPIRED.to_vec())
+        );
+        assert_eq!(contract.get_nonce(developer).unwrap(), U256::from(2));
+    }
+
     /// Test: Admin cannot approve their own app
     ///
     /// Edge case: What if an admin registers an app?
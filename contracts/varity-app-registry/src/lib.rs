@@ -1,4 +1,4 @@
-#![cfg_attr(not(feature = "export-abi"), no_main)]
+#![cfg_attr(not(any(test, feature = "export-abi")), no_main)]
 extern crate alloc;
 
 use alloc::string::String;
@@ -18,23 +18,41 @@ sol! {
     event AppRejected(uint256 indexed app_id, address indexed approver);
     event AdminAdded(address indexed admin);
     event AdminRemoved(address indexed admin);
+    event TierUpdated(uint256 indexed tier_id, string name, uint256 monthly_price, uint256 tx_quota, bool is_active);
+    event TierChanged(uint256 indexed app_id, uint256 old_tier, uint256 new_tier, uint256 effective_at);
 
+    #[derive(Debug)]
     error Unauthorized();
+    #[derive(Debug)]
     error InvalidInput();
+    #[derive(Debug)]
     error AppNotFound();
+    #[derive(Debug)]
     error AlreadyApproved();
+    #[derive(Debug)]
     error InvalidTier();
+    #[derive(Debug)]
+    error TiersAlreadySeeded();
 }
 
-#[derive(SolidityError)]
+#[derive(SolidityError, Debug)]
 pub enum RegistryError {
     Unauthorized(Unauthorized),
     InvalidInput(InvalidInput),
     AppNotFound(AppNotFound),
     AlreadyApproved(AlreadyApproved),
     InvalidTier(InvalidTier),
+    TiersAlreadySeeded(TiersAlreadySeeded),
 }
 
+/// Default tier catalog: (name, monthly price in USDC, monthly tx quota)
+const DEFAULT_TIERS: [(&str, u64, u64); 4] = [
+    ("free", 0, 0),
+    ("starter", 49_000_000, 50_000),
+    ("growth", 99_000_000, 250_000),
+    ("enterprise", 199_000_000, 1_000_000),
+];
+
 // ============================================================================
 // Storage Structure
 // ============================================================================
@@ -62,10 +80,21 @@ sol_storage! {
         mapping(uint256 => string) app_repos;
         mapping(uint256 => uint256) app_prices;
         mapping(uint256 => bool) app_is_approved;
-        mapping(uint256 => string) app_tiers; // Infrastructure tier: "free", "starter", "growth", "enterprise"
+        mapping(uint256 => string) app_tiers; // Infrastructure tier name (must exist in the tier catalog)
+        mapping(uint256 => uint256) app_tier_effective_at; // Timestamp from which the current tier applies
+
+        // Tier catalog (tier_id => field, IDs 0..tier_count)
+        uint256 tier_count;
+        mapping(uint256 => string) tier_names;
+        mapping(uint256 => uint256) tier_monthly_prices; // USDC (6 decimals)
+        mapping(uint256 => uint256) tier_tx_quotas; // Monthly transactions (0 = unmetered)
+        mapping(uint256 => bool) tier_is_active;
 
         // List of pending app IDs awaiting approval
         uint256[] pending_apps;
+
+        // Owner: manages the tier catalog
+        address owner;
     }
 }
 
@@ -75,11 +104,45 @@ sol_storage! {
 
 #[public]
 impl VarityAppRegistry {
-    /// Constructor - sets deployer as initial admin
+    /// Constructor - sets deployer as owner and initial admin
     pub fn init(&mut self) -> Result<(), RegistryError> {
-        let deployer = contract::msg_sender();
+        let deployer = self.__stylus_host.msg_sender();
+        self.owner.set(deployer);
         self.admins.insert(deployer, true);
-        AdminAdded { admin: deployer }.emit();
+        self.vm().log(AdminAdded { admin: deployer });
+
+        self.seed_tiers();
+        Ok(())
+    }
+
+    /// Claim ownership of a registry deployed before it had an owner (admin only, once)
+    pub fn claim_owner(&mut self) -> Result<(), RegistryError> {
+        // CHECKS
+        let caller = self.__stylus_host.msg_sender();
+        if !self.admins.get(caller) || self.owner.get() != Address::ZERO {
+            return Err(RegistryError::Unauthorized(Unauthorized {}));
+        }
+
+        // EFFECTS
+        self.owner.set(caller);
+
+        Ok(())
+    }
+
+    /// Seed the default tier catalog on a registry deployed before it
+    /// existed (owner only, while the catalog is empty)
+    pub fn seed_default_tiers(&mut self) -> Result<(), RegistryError> {
+        // CHECKS
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(RegistryError::Unauthorized(Unauthorized {}));
+        }
+        if self.tier_count.get() != U256::ZERO {
+            return Err(RegistryError::TiersAlreadySeeded(TiersAlreadySeeded {}));
+        }
+
+        // EFFECTS
+        self.seed_tiers();
+
         Ok(())
     }
 
@@ -98,7 +161,7 @@ impl VarityAppRegistry {
         demo_url: String,
         repo_url: String,
         price_usdc: U256,
-        tier: String, // Infrastructure tier: "free", "starter", "growth", "enterprise"
+        tier: String, // Infrastructure tier name from the tier catalog
     ) -> Result<U256, RegistryError> {
         // CHECKS: Validate inputs
         if name.is_empty() {
@@ -110,8 +173,8 @@ impl VarityAppRegistry {
         if app_type.is_empty() {
             return Err(RegistryError::InvalidInput(InvalidInput {}));
         }
-        // Validate tier - must be one of: free, starter, growth, enterprise
-        if tier != "free" && tier != "starter" && tier != "growth" && tier != "enterprise" {
+        // Validate tier against the catalog
+        if self.find_active_tier(&tier).is_none() {
             return Err(RegistryError::InvalidTier(InvalidTier {}));
         }
 
@@ -120,7 +183,7 @@ impl VarityAppRegistry {
         let next_id = app_id + U256::from(1);
         self.app_count.set(next_id);
 
-        let developer = contract::msg_sender();
+        let developer = self.__stylus_host.msg_sender();
 
         // Store app data
         self.app_names.setter(app_id).set_str(name.clone());
@@ -136,18 +199,19 @@ impl VarityAppRegistry {
         self.app_prices.insert(app_id, price_usdc);
         self.app_is_approved.insert(app_id, false);
         self.app_tiers.setter(app_id).set_str(tier.clone());
+        self.app_tier_effective_at.insert(app_id, U256::from(self.__stylus_host.block_timestamp()));
 
         // Add to pending queue
         self.pending_apps.push(app_id);
 
         // INTERACTIONS: Emit event
-        AppRegistered {
+        self.vm().log(AppRegistered {
             app_id,
             developer,
             name,
             app_type,
             tier,
-        }.emit();
+        });
 
         Ok(app_id)
     }
@@ -155,7 +219,7 @@ impl VarityAppRegistry {
     /// Approve an app (admin only)
     pub fn approve_app(&mut self, app_id: U256) -> Result<(), RegistryError> {
         // CHECKS
-        if !self.admins.get(contract::msg_sender()) {
+        if !self.admins.get(self.__stylus_host.msg_sender()) {
             return Err(RegistryError::Unauthorized(Unauthorized {}));
         }
 
@@ -175,10 +239,10 @@ impl VarityAppRegistry {
         self.remove_from_pending(app_id);
 
         // INTERACTIONS
-        AppApproved {
+        self.vm().log(AppApproved {
             app_id,
-            approver: contract::msg_sender(),
-        }.emit();
+            approver: self.__stylus_host.msg_sender(),
+        });
 
         Ok(())
     }
@@ -186,7 +250,7 @@ impl VarityAppRegistry {
     /// Reject an app (admin only)
     pub fn reject_app(&mut self, app_id: U256) -> Result<(), RegistryError> {
         // CHECKS
-        if !self.admins.get(contract::msg_sender()) {
+        if !self.admins.get(self.__stylus_host.msg_sender()) {
             return Err(RegistryError::Unauthorized(Unauthorized {}));
         }
 
@@ -200,10 +264,100 @@ impl VarityAppRegistry {
         self.remove_from_pending(app_id);
 
         // INTERACTIONS
-        AppRejected {
+        self.vm().log(AppRejected {
             app_id,
-            approver: contract::msg_sender(),
-        }.emit();
+            approver: self.__stylus_host.msg_sender(),
+        });
+
+        Ok(())
+    }
+
+    /// Change an app's infrastructure tier (developer only)
+    /// The new tier applies from the current block timestamp; TierChanged
+    /// carries catalog tier IDs
+    pub fn change_tier(&mut self, app_id: U256, new_tier: String) -> Result<(), RegistryError> {
+        // CHECKS
+        let developer = self.app_developers.get(app_id);
+        if developer == Address::ZERO {
+            return Err(RegistryError::AppNotFound(AppNotFound {}));
+        }
+        if developer != self.__stylus_host.msg_sender() {
+            return Err(RegistryError::Unauthorized(Unauthorized {}));
+        }
+        let Some(new_tier_id) = self.find_active_tier(&new_tier) else {
+            return Err(RegistryError::InvalidTier(InvalidTier {}));
+        };
+
+        let old_tier = self.app_tiers.getter(app_id).get_string();
+        if old_tier == new_tier {
+            return Err(RegistryError::InvalidTier(InvalidTier {}));
+        }
+        let Some(old_tier_id) = self.find_tier(&old_tier) else {
+            return Err(RegistryError::InvalidTier(InvalidTier {}));
+        };
+
+        // EFFECTS
+        let effective_at = U256::from(self.__stylus_host.block_timestamp());
+        self.app_tiers.setter(app_id).set_str(new_tier);
+        self.app_tier_effective_at.insert(app_id, effective_at);
+
+        // INTERACTIONS
+        self.vm().log(TierChanged {
+            app_id,
+            old_tier: old_tier_id,
+            new_tier: new_tier_id,
+            effective_at,
+        });
+
+        Ok(())
+    }
+
+    /// Add or update a tier in the catalog (owner only)
+    /// `tier_id` must be an existing ID or `tier_count` (to append a new tier);
+    /// apps store their tier by name, so an existing tier keeps its name
+    pub fn set_tier(
+        &mut self,
+        tier_id: U256,
+        name: String,
+        monthly_price: U256,
+        tx_quota: U256,
+        is_active: bool,
+    ) -> Result<(), RegistryError> {
+        // CHECKS
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(RegistryError::Unauthorized(Unauthorized {}));
+        }
+
+        let tier_count = self.tier_count.get();
+        if tier_id > tier_count {
+            return Err(RegistryError::InvalidTier(InvalidTier {}));
+        }
+        if name.is_empty() || name.len() > 32 {
+            return Err(RegistryError::InvalidInput(InvalidInput {}));
+        }
+        // Names are set once, on append, and stay unique
+        if tier_id < tier_count {
+            if self.tier_names.getter(tier_id).get_string() != name {
+                return Err(RegistryError::InvalidTier(InvalidTier {}));
+            }
+        } else if self.find_tier(&name).is_some() {
+            return Err(RegistryError::InvalidTier(InvalidTier {}));
+        }
+
+        // EFFECTS
+        self.store_tier(tier_id, &name, monthly_price, tx_quota, is_active);
+        if tier_id == tier_count {
+            self.tier_count.set(tier_count + U256::from(1));
+        }
+
+        // INTERACTIONS
+        self.vm().log(TierUpdated {
+            tier_id,
+            name,
+            monthly_price,
+            tx_quota,
+            is_active,
+        });
 
         Ok(())
     }
@@ -211,7 +365,7 @@ impl VarityAppRegistry {
     /// Add admin (admin only)
     pub fn add_admin(&mut self, new_admin: Address) -> Result<(), RegistryError> {
         // CHECKS
-        if !self.admins.get(contract::msg_sender()) {
+        if !self.admins.get(self.__stylus_host.msg_sender()) {
             return Err(RegistryError::Unauthorized(Unauthorized {}));
         }
 
@@ -219,7 +373,7 @@ impl VarityAppRegistry {
         self.admins.insert(new_admin, true);
 
         // INTERACTIONS
-        AdminAdded { admin: new_admin }.emit();
+        self.vm().log(AdminAdded { admin: new_admin });
 
         Ok(())
     }
@@ -227,7 +381,7 @@ impl VarityAppRegistry {
     /// Remove admin (admin only)
     pub fn remove_admin(&mut self, admin_to_remove: Address) -> Result<(), RegistryError> {
         // CHECKS
-        if !self.admins.get(contract::msg_sender()) {
+        if !self.admins.get(self.__stylus_host.msg_sender()) {
             return Err(RegistryError::Unauthorized(Unauthorized {}));
         }
 
@@ -235,7 +389,7 @@ impl VarityAppRegistry {
         self.admins.insert(admin_to_remove, false);
 
         // INTERACTIONS
-        AdminRemoved { admin: admin_to_remove }.emit();
+        self.vm().log(AdminRemoved { admin: admin_to_remove });
 
         Ok(())
     }
@@ -275,6 +429,35 @@ impl VarityAppRegistry {
         self.app_tiers.getter(app_id).get_string()
     }
 
+    /// Get the timestamp from which an app's current tier applies
+    pub fn get_app_tier_effective_at(&self, app_id: U256) -> U256 {
+        self.app_tier_effective_at.get(app_id)
+    }
+
+    /// Get the owner (manages the tier catalog)
+    pub fn get_owner(&self) -> Address {
+        self.owner.get()
+    }
+
+    /// Get tier count
+    pub fn get_tier_count(&self) -> U256 {
+        self.tier_count.get()
+    }
+
+    /// Get tier (name, monthly price, transaction quota, active)
+    pub fn get_tier(&self, tier_id: U256) -> Result<(String, U256, U256, bool), RegistryError> {
+        if tier_id >= self.tier_count.get() {
+            return Err(RegistryError::InvalidTier(InvalidTier {}));
+        }
+
+        Ok((
+            self.tier_names.getter(tier_id).get_string(),
+            self.tier_monthly_prices.get(tier_id),
+            self.tier_tx_quotas.get(tier_id),
+            self.tier_is_active.get(tier_id),
+        ))
+    }
+
     /// Get pending apps count
     pub fn get_pending_count(&self) -> U256 {
         U256::from(self.pending_apps.len())
@@ -282,7 +465,7 @@ impl VarityAppRegistry {
 
     /// Get pending app ID by index
     pub fn get_pending_app(&self, index: U256) -> Result<U256, RegistryError> {
-        let idx = index.try_into().map_err(|_| RegistryError::InvalidInput(InvalidInput))?;
+        let idx: usize = index.try_into().map_err(|_| RegistryError::InvalidInput(InvalidInput {}))?;
 
         if idx >= self.pending_apps.len() {
             return Err(RegistryError::AppNotFound(AppNotFound {}));
@@ -297,6 +480,42 @@ impl VarityAppRegistry {
 // ============================================================================
 
 impl VarityAppRegistry {
+    /// Write the default tiers as IDs 0..4, emitting TierUpdated for each
+    fn seed_tiers(&mut self) {
+        for (tier_id, (name, monthly_price, tx_quota)) in DEFAULT_TIERS.iter().enumerate() {
+            self.store_tier(U256::from(tier_id), name, U256::from(*monthly_price), U256::from(*tx_quota), true);
+            self.vm().log(TierUpdated {
+                tier_id: U256::from(tier_id),
+                name: name.to_string(),
+                monthly_price: U256::from(*monthly_price),
+                tx_quota: U256::from(*tx_quota),
+                is_active: true,
+            });
+        }
+        self.tier_count.set(U256::from(DEFAULT_TIERS.len()));
+    }
+
+    /// Write a tier's fields to the catalog
+    fn store_tier(&mut self, tier_id: U256, name: &str, monthly_price: U256, tx_quota: U256, is_active: bool) {
+        self.tier_names.setter(tier_id).set_str(name);
+        self.tier_monthly_prices.insert(tier_id, monthly_price);
+        self.tier_tx_quotas.insert(tier_id, tx_quota);
+        self.tier_is_active.insert(tier_id, is_active);
+    }
+
+    /// Look up a tier ID by name
+    fn find_tier(&self, name: &str) -> Option<U256> {
+        let tier_count: u64 = self.tier_count.get().to();
+        (0..tier_count)
+            .map(U256::from)
+            .find(|id| self.tier_names.getter(*id).get_string() == name)
+    }
+
+    /// Look up an active tier ID by name
+    fn find_active_tier(&self, name: &str) -> Option<U256> {
+        self.find_tier(name).filter(|id| self.tier_is_active.get(*id))
+    }
+
     /// Remove app from pending queue
     fn remove_from_pending(&mut self, app_id: U256) {
         let len = self.pending_apps.len();
@@ -325,10 +544,106 @@ impl VarityAppRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use stylus_sdk::testing::*;
+
+    fn mock_address(seed: u8) -> Address {
+        Address::from([seed; 20])
+    }
+
+    /// Deploy and initialize the contract with `owner` as owner and first admin
+    fn setup(vm: &TestVM, owner: Address) -> VarityAppRegistry {
+        let mut contract = VarityAppRegistry::from(vm);
+        vm.set_sender(owner);
+        contract.init().unwrap();
+        contract
+    }
+
+    /// Register a minimal app on `tier` as `developer`
+    fn register(vm: &TestVM, contract: &mut VarityAppRegistry, developer: Address, tier: &str) -> Result<U256, RegistryError> {
+        vm.set_sender(developer);
+        contract.register_app(
+            "App".into(), "An app".into(), "web".into(), "tools".into(), "1.0.0".into(),
+            String::new(), String::new(), String::new(), String::new(), U256::ZERO, tier.into(),
+        )
+    }
+
+    #[test]
+    fn test_tier_catalog_owner_gated() {
+        let vm = TestVM::default();
+        let owner = mock_address(1);
+        let admin = mock_address(2);
+        let developer = mock_address(3);
+        let mut contract = setup(&vm, owner);
+        contract.add_admin(admin).unwrap();
+        assert_eq!(contract.get_tier_count(), U256::from(4));
+        // AdminAdded and TierUpdated for each seeded tier on init, then AdminAdded
+        assert_eq!(vm.get_emitted_logs().len(), 6);
+
+        // Admins do not manage the catalog
+        vm.set_sender(admin);
+        assert!(matches!(
+            contract.set_tier(U256::from(4), "platinum".into(), U256::from(499_000_000u64), U256::from(5_000_000u64), true),
+            Err(RegistryError::Unauthorized(_))
+        ));
+        assert!(matches!(contract.claim_owner(), Err(RegistryError::Unauthorized(_))));
+        assert!(matches!(register(&vm, &mut contract, developer, "platinum"), Err(RegistryError::InvalidTier(_))));
+
+        vm.set_sender(owner);
+        contract
+            .set_tier(U256::from(4), "platinum".into(), U256::from(499_000_000u64), U256::from(5_000_000u64), true)
+            .unwrap();
+        assert!(matches!(
+            contract.set_tier(U256::from(6), "diamond".into(), U256::ZERO, U256::ZERO, true),
+            Err(RegistryError::InvalidTier(_))
+        ));
+        assert!(matches!(
+            contract.set_tier(U256::from(5), "growth".into(), U256::ZERO, U256::ZERO, true),
+            Err(RegistryError::InvalidTier(_))
+        ));
+        // Renaming would strand apps registered under the old name
+        assert!(matches!(
+            contract.set_tier(U256::from(1), "basic".into(), U256::from(49_000_000u64), U256::from(50_000u64), true),
+            Err(RegistryError::InvalidTier(_))
+        ));
+        let app_id = register(&vm, &mut contract, developer, "platinum").unwrap();
+
+        // Only the developer changes the app's tier, and only to an active one
+        vm.set_block_timestamp(5_000);
+        vm.set_sender(owner);
+        assert!(matches!(contract.change_tier(app_id, "growth".into()), Err(RegistryError::Unauthorized(_))));
+        vm.set_sender(developer);
+        contract.change_tier(app_id, "growth".into()).unwrap();
+        assert_eq!(contract.get_app_tier(app_id), "growth");
+        assert_eq!(contract.get_app_tier_effective_at(app_id), U256::from(5_000));
+        assert!(matches!(contract.change_tier(app_id, "missing".into()), Err(RegistryError::InvalidTier(_))));
+    }
 
     #[test]
-    fn test_compile() {
-        // Basic compile test
-        assert!(true);
+    fn test_legacy_registry_claims_owner_and_seeds_once() {
+        let vm = TestVM::default();
+        let admin = mock_address(2);
+        let stranger = mock_address(3);
+
+        // A registry deployed before the owner and catalog existed
+        let mut contract = VarityAppRegistry::from(&vm);
+        contract.admins.insert(admin, true);
+        assert_eq!(contract.get_tier_count(), U256::ZERO);
+
+        vm.set_sender(stranger);
+        assert!(matches!(contract.claim_owner(), Err(RegistryError::Unauthorized(_))));
+        vm.set_sender(admin);
+        assert!(matches!(contract.seed_default_tiers(), Err(RegistryError::Unauthorized(_))));
+        contract.claim_owner().unwrap();
+        assert_eq!(contract.get_owner(), admin);
+        assert!(matches!(contract.claim_owner(), Err(RegistryError::Unauthorized(_))));
+
+        contract.seed_default_tiers().unwrap();
+        assert_eq!(contract.get_tier_count(), U256::from(4));
+        assert_eq!(
+            contract.get_tier(U256::from(1)).unwrap(),
+            ("starter".into(), U256::from(49_000_000u64), U256::from(50_000u64), true)
+        );
+        assert_eq!(vm.get_emitted_logs().len(), 4);
+        assert!(matches!(contract.seed_default_tiers(), Err(RegistryError::TiersAlreadySeeded(_))));
     }
 }
\ No newline at end of file
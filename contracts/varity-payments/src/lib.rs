@@ -5,20 +5,58 @@
 //!
 //! Key Features:
 //! - App purchases with 90/10 revenue split (90% developer, 10% Varity)
-//! - Developer infrastructure billing (100% to Varity)
-//! - Uses ERC-20 USDC on Arbitrum One (requires user to approve() contract first)
+//! - Owner-managed platform fee schedules: per-app and per-developer
+//!   overrides and volume tiers, never above the default 10%
+//! - Pull-based payouts: purchase proceeds are credited to an internal ledger
+//!   and withdrawn by the developer (or swept to the treasury)
+//! - Scheduled price changes (with notice for subscription price increases),
+//!   per-app price history, and a buyer-supplied max price on purchase_app
+//! - Free apps (one-time price of zero) claimed into the same entitlement
+//!   records as paid purchases
+//! - Refunds within a per-app refund window (developer or admin initiated)
+//! - Pricing deactivation and reactivation: lifetime licenses are kept, and
+//!   subscribers keep access for their paid period or claim a pro-rata refund
+//! - Optional per-app escrow: developer shares unlock after a hold period,
+//!   during which a buyer can open a dispute that an admin resolves
+//! - Free trials for subscription apps (one per address per app), converted
+//!   by purchasing
+//! - Per-app revenue splits between multiple payees (owner-confirmed)
+//! - Affiliate referrals: a developer-set commission taken from the
+//!   developer share and credited to registered affiliates
+//! - ERC-1155 license tokens (token ID = app ID) minted on purchase, with
+//!   developer opt-in transferability and a resale royalty split 90/10
+//! - Team seats: bulk purchases with volume discounts, assigned to members
+//!   by the purchasing organization
+//! - Purchasing on behalf of another address (gifts, team seats, custodial checkout)
+//! - Developer-managed coupons (percent or fixed discount, redemption caps,
+//!   expiry, optional buyer allowlist)
+//! - Per-app and per-developer revenue analytics readable without an indexer
+//! - Per-user library of held licenses (paginated, with purchase time and
+//!   subscription expiry) for the store's "My Apps" page
+//! - Developer infrastructure billing (100% to Varity), ad hoc or against
+//!   invoices issued by the billing role
+//! - Owner-managed tier catalog with invoices prorated across tier changes
+//! - Prepaid infrastructure credit that the billing role draws bills from
+//! - Usage metering: per-period transaction counts from a reporter role
+//!   (direct or signed batches), with overage priced above the tier quota
+//! - Gasless purchases: EIP-712 signed requests relayed by a third party,
+//!   with per-signer nonces and deadlines
+//! - Uses ERC-20 USDC on Arbitrum One (requires user to approve() contract first,
+//!   or an EIP-2612 permit signature via the *_with_permit functions)
 //!
 //! Revenue Streams:
 //! - Developer infrastructure costs - via pay_bill()
 //! - App Store Commission (10%) - via purchase_app()
 
-#![cfg_attr(not(feature = "export-abi"), no_main)]
+#![cfg_attr(not(any(test, feature = "export-abi")), no_main)]
 extern crate alloc;
 
 use stylus_sdk::prelude::*;
 use stylus_sdk::call::RawCall;
-use alloy_primitives::{Address, U256};
-use alloy_sol_types::{sol, SolCall};
+use stylus_sdk::crypto::keccak;
+use alloy_primitives::{Address, Bytes, FixedBytes, B256, U256};
+use alloy_sol_types::{sol, Eip712Domain, SolCall, SolStruct, SolValue};
+use alloc::borrow::Cow;
 use alloc::vec::Vec;
 
 // ============ ERC-20 Function Signatures ============
@@ -26,6 +64,47 @@ use alloc::vec::Vec;
 sol! {
     /// ERC-20 transferFrom for pulling USDC from user
     function transferFrom(address from, address to, uint256 amount) external returns (bool);
+
+    /// ERC-20 transfer for paying out tokens held by this contract
+    function transfer(address to, uint256 amount) external returns (bool);
+
+    /// ERC-20 allowance, checked when a permit call fails
+    function allowance(address owner, address spender) external view returns (uint256);
+
+    /// EIP-2612 permit for approving this contract by signature
+    function permit(address owner, address spender, uint256 value, uint256 deadline, uint8 v, bytes32 r, bytes32 s) external;
+}
+
+// ============ ERC-1155 Receiver Hooks ============
+
+sol! {
+    /// Called on contract recipients of a single license transfer
+    function onERC1155Received(address operator, address from, uint256 id, uint256 value, bytes data) external returns (bytes4);
+
+    /// Called on contract recipients of a batch license transfer
+    function onERC1155BatchReceived(address operator, address from, uint256[] ids, uint256[] values, bytes data) external returns (bytes4);
+}
+
+// ============ Signed Payloads ============
+
+sol! {
+    /// Usage batch signed by a reporter (see report_usage_batch)
+    struct UsageReportBatch {
+        address verifying_contract;
+        uint256 chain_id;
+        uint64[] app_ids;
+        uint64[] period_hashes;
+        uint64[] tx_counts;
+    }
+
+    /// EIP-712 request for purchase_app_by_sig
+    struct PurchaseApp {
+        uint64 app_id;
+        address buyer;
+        uint256 max_price;
+        uint256 nonce;
+        uint256 deadline;
+    }
 }
 
 // ============ Constants ============
@@ -39,8 +118,8 @@ const BPS_DENOMINATOR: u64 = 10000;
 /// Varity Treasury wallet - receives all platform fees
 /// Address: 0xA0b83bBeF45FeE8c8E158b25b736E05eBd51b793
 const TREASURY: [u8; 20] = [
-    0xA0, 0xb8, 0x3b, 0xBe, 0xF4, 0x5F, 0xeE, 0x8c, 0x8E, 0x15,
-    0x8b, 0x25, 0xb7, 0x36, 0xE0, 0x5e, 0xBd, 0x51, 0xb7, 0x93
+    0xa0, 0xb8, 0x3b, 0xbe, 0xf4, 0x5f, 0xee, 0x8c, 0x8e, 0x15,
+    0x8b, 0x25, 0xb7, 0x36, 0xe0, 0x5e, 0xbd, 0x51, 0xb7, 0x93
 ];
 
 /// USDC token address on Arbitrum One
@@ -60,6 +139,134 @@ const ERROR_INSUFFICIENT_PAYMENT: &[u8] = b"InsufficientPayment";
 const ERROR_TRANSFER_FAILED: &[u8] = b"TransferFailed";
 const ERROR_ALREADY_PURCHASED: &[u8] = b"AlreadyPurchased";
 const ERROR_INVALID_PERIOD: &[u8] = b"InvalidPeriod";
+const ERROR_INSUFFICIENT_BALANCE: &[u8] = b"InsufficientBalance";
+const ERROR_INVALID_RECIPIENT: &[u8] = b"InvalidRecipient";
+const ERROR_NOT_PURCHASED: &[u8] = b"NotPurchased";
+const ERROR_REFUND_WINDOW_CLOSED: &[u8] = b"RefundWindowClosed";
+const ERROR_ARITHMETIC_OVERFLOW: &[u8] = b"ArithmeticOverflow";
+const ERROR_INVALID_TIER: &[u8] = b"InvalidTier";
+const ERROR_INVALID_INPUT: &[u8] = b"InvalidInput";
+const ERROR_TIERS_SEEDED: &[u8] = b"TiersAlreadySeeded";
+const ERROR_INVOICE_NOT_FOUND: &[u8] = b"InvoiceNotFound";
+const ERROR_INVOICE_OVERPAYMENT: &[u8] = b"InvoiceOverpayment";
+const ERROR_INVOICE_DEVELOPER_MISMATCH: &[u8] = b"InvoiceDeveloperMismatch";
+const ERROR_INVALID_SIGNATURE: &[u8] = b"InvalidSignature";
+const ERROR_INVALID_BATCH: &[u8] = b"InvalidBatch";
+const ERROR_STALE_USAGE: &[u8] = b"StaleUsage";
+const ERROR_PERMIT_FAILED: &[u8] = b"PermitFailed";
+const ERROR_SIGNATURE_EXPIRED: &[u8] = b"SignatureExpired";
+const ERROR_INVALID_COUPON: &[u8] = b"InvalidCoupon";
+const ERROR_COUPON_EXPIRED: &[u8] = b"CouponExpired";
+const ERROR_COUPON_EXHAUSTED: &[u8] = b"CouponExhausted";
+const ERROR_COUPON_NOT_ALLOWED: &[u8] = b"CouponNotAllowed";
+const ERROR_INVALID_SPLIT: &[u8] = b"InvalidSplit";
+const ERROR_NO_PENDING_SPLIT: &[u8] = b"NoPendingSplit";
+const ERROR_INVALID_REFERRER: &[u8] = b"InvalidReferrer";
+const ERROR_INVALID_REFERRAL_SHARE: &[u8] = b"InvalidReferralShare";
+const ERROR_NOT_SUBSCRIPTION: &[u8] = b"NotSubscription";
+const ERROR_TRIAL_UNAVAILABLE: &[u8] = b"TrialUnavailable";
+const ERROR_TRIAL_USED: &[u8] = b"TrialAlreadyUsed";
+const ERROR_NOT_TRANSFERABLE: &[u8] = b"LicenseNotTransferable";
+const ERROR_LICENSE_TRANSFERRED: &[u8] = b"LicenseTransferred";
+const ERROR_INVALID_RECEIVER: &[u8] = b"InvalidReceiver";
+const ERROR_INVALID_ROYALTY: &[u8] = b"InvalidRoyalty";
+const ERROR_INVALID_QUANTITY: &[u8] = b"InvalidQuantity";
+const ERROR_INVALID_DISCOUNTS: &[u8] = b"InvalidDiscounts";
+const ERROR_NO_FREE_SEATS: &[u8] = b"NoFreeSeats";
+const ERROR_SEAT_ASSIGNED: &[u8] = b"SeatAlreadyAssigned";
+const ERROR_SEAT_NOT_ASSIGNED: &[u8] = b"SeatNotAssigned";
+const ERROR_INVALID_FEE: &[u8] = b"InvalidFee";
+const ERROR_NOTICE_TOO_SHORT: &[u8] = b"NoticePeriodTooShort";
+const ERROR_NO_PENDING_PRICE: &[u8] = b"NoPendingPriceChange";
+const ERROR_PRICE_ABOVE_MAX: &[u8] = b"PriceAboveMax";
+const ERROR_INVALID_HOLD: &[u8] = b"InvalidHoldPeriod";
+const ERROR_NOT_ESCROWED: &[u8] = b"NotEscrowed";
+const ERROR_DISPUTE_WINDOW_CLOSED: &[u8] = b"DisputeWindowClosed";
+const ERROR_ALREADY_DISPUTED: &[u8] = b"AlreadyDisputed";
+const ERROR_NOT_DISPUTED: &[u8] = b"NotDisputed";
+const ERROR_APP_ACTIVE: &[u8] = b"AppPricingActive";
+const ERROR_REFUND_UNAVAILABLE: &[u8] = b"RefundUnavailable";
+const ERROR_APP_NOT_FREE: &[u8] = b"AppNotFree";
+const ERROR_SEATS_EXPIRED: &[u8] = b"SeatsExpired";
+const ERROR_PRICING_SET: &[u8] = b"PricingAlreadySet";
+const ERROR_REFUNDS_OUTSTANDING: &[u8] = b"SubscriberRefundsOutstanding";
+
+/// ecrecover precompile
+const ECRECOVER: [u8; 20] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 1
+];
+
+/// Maximum payees in an app's revenue split
+const MAX_PAYEES: usize = 10;
+
+/// Maximum referral commission (50% of the developer share)
+const MAX_REFERRAL_BPS: u64 = 5000;
+
+/// Maximum license resale royalty (25% of the sale price)
+const MAX_ROYALTY_BPS: u64 = 2500;
+
+/// Maximum volume-discount tiers per app
+const MAX_SEAT_DISCOUNTS: usize = 10;
+
+/// Maximum volume-based platform fee tiers
+const MAX_FEE_TIERS: usize = 10;
+
+/// ERC-165 interface IDs: ERC-165, ERC-1155, ERC-2981
+const INTERFACE_ERC165: [u8; 4] = [0x01, 0xff, 0xc9, 0xa7];
+const INTERFACE_ERC1155: [u8; 4] = [0xd9, 0xb6, 0x7a, 0x26];
+const INTERFACE_ERC2981: [u8; 4] = [0x2a, 0x55, 0x20, 0x5a];
+
+/// Maximum entries in a single usage batch
+const MAX_USAGE_BATCH: usize = 100;
+
+/// Seconds per day (refund windows and subscription intervals are set in days)
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Minimum notice for a subscription price increase
+const PRICE_NOTICE_SECONDS: u64 = 7 * SECONDS_PER_DAY;
+
+/// Maximum escrow hold period (bounds the days scanned for unlocked escrow)
+const MAX_ESCROW_HOLD_DAYS: u64 = 90;
+
+/// Maximum number of days summed by a single revenue range query
+const MAX_REVENUE_RANGE_DAYS: u64 = 366;
+
+/// Default tier catalog: (name, monthly price in USDC, monthly tx quota).
+/// Seeded as IDs 0..4, matching VarityAppRegistry.
+const DEFAULT_TIERS: [(&str, u64, u64); 4] = [
+    ("free", 0, 0),
+    ("starter", 49_000_000, 50_000),
+    ("growth", 99_000_000, 250_000),
+    ("enterprise", 199_000_000, 1_000_000),
+];
+
+// ============ Checked Arithmetic ============
+
+/// Add two amounts, failing with ArithmeticOverflow instead of wrapping
+fn checked_add(a: U256, b: U256) -> Result<U256, Vec<u8>> {
+    a.checked_add(b).ok_or_else(|| ERROR_ARITHMETIC_OVERFLOW.to_vec())
+}
+
+/// Subtract two amounts, failing with ArithmeticOverflow instead of wrapping
+fn checked_sub(a: U256, b: U256) -> Result<U256, Vec<u8>> {
+    a.checked_sub(b).ok_or_else(|| ERROR_ARITHMETIC_OVERFLOW.to_vec())
+}
+
+/// Multiply two amounts, failing with ArithmeticOverflow instead of wrapping
+fn checked_mul(a: U256, b: U256) -> Result<U256, Vec<u8>> {
+    a.checked_mul(b).ok_or_else(|| ERROR_ARITHMETIC_OVERFLOW.to_vec())
+}
+
+/// Narrow a stored value to u64 for a view, failing with ArithmeticOverflow instead of panicking
+fn to_u64(value: U256) -> Result<u64, Vec<u8>> {
+    value.try_into().map_err(|_| ERROR_ARITHMETIC_OVERFLOW.to_vec())
+}
+
+/// Share of an amount in basis points (rounded down)
+fn bps_of(amount: U256, bps: u64) -> Result<U256, Vec<u8>> {
+    Ok(checked_mul(amount, U256::from(bps))? / U256::from(BPS_DENOMINATOR))
+}
 
 // ============ Events (Solidity ABI compatible) ============
 
@@ -73,14 +280,53 @@ sol! {
         uint256 interval_days
     );
 
-    /// Emitted when an app is purchased
+    /// Emitted when a user claims a free app (recorded like a purchase)
+    event AppClaimed(
+        uint256 indexed app_id,
+        address indexed user,
+        uint256 timestamp
+    );
+
+    /// Emitted when a developer stops selling an app
+    event AppPricingDeactivated(
+        uint256 indexed app_id,
+        address indexed developer,
+        bool refund_subscribers
+    );
+
+    /// Emitted when a developer resumes selling an app at its kept price
+    event AppPricingReactivated(
+        uint256 indexed app_id,
+        address indexed developer,
+        uint256 price_usdc
+    );
+
+    /// Emitted when a subscriber of a deactivated app claims a pro-rata refund
+    event SubscriptionRefunded(
+        uint256 indexed app_id,
+        address indexed subscriber,
+        uint256 amount,
+        uint256 timestamp
+    );
+
+    /// Emitted when a subscriber pays for another interval
+    event SubscriptionRenewed(
+        uint256 indexed app_id,
+        address indexed subscriber,
+        uint256 amount,
+        uint256 paid_through
+    );
+
+    /// Emitted when an app is purchased (payer pays, recipient receives access)
     event AppPurchased(
         uint256 indexed app_id,
-        address indexed buyer,
+        address indexed recipient,
         address indexed developer,
+        address payer,
         uint256 total_amount,
         uint256 developer_share,
         uint256 platform_fee,
+        uint256 fee_bps,
         uint256 timestamp
     );
 
@@ -92,390 +338,6322 @@ sol! {
         uint256 period_hash,
         uint256 timestamp
     );
-}
 
-// ============ Storage ============
+    /// Emitted when the billing role issues an infrastructure invoice
+    event InvoiceIssued(
+        uint256 indexed invoice_id,
+        uint256 indexed app_id,
+        address indexed developer,
+        uint256 period_hash,
+        uint256 tier,
+        uint256 amount,
+        uint256 due_date
+    );
 
-sol_storage! {
-    #[entrypoint]
-    pub struct VarityPayments {
-        /// Contract owner
-        address owner;
+    /// Emitted when a payment (full or partial) is made against an invoice
+    event InvoicePaid(
+        uint256 indexed invoice_id,
+        uint256 indexed app_id,
+        address indexed payer,
+        uint256 amount,
+        uint256 total_paid,
+        bool fully_paid
+    );
 
-        /// App pricing: app_id => price in USDC (6 decimals)
-        mapping(uint256 => uint256) app_prices;
+    /// Emitted when the owner adds or updates a tier in the catalog
+    event TierUpdated(
+        uint256 indexed tier_id,
+        string name,
+        uint256 monthly_price,
+        uint256 tx_quota,
+        bool is_active
+    );
 
-        /// App pricing: app_id => developer address
-        mapping(uint256 => address) app_developers;
+    /// Emitted when an app's tier change is recorded for billing
+    event TierChanged(
+        uint256 indexed app_id,
+        uint256 old_tier,
+        uint256 new_tier,
+        uint256 effective_at
+    );
 
-        /// App pricing: app_id => is subscription (vs one-time)
-        mapping(uint256 => bool) app_is_subscription;
+    /// Emitted when the owner grants or revokes the billing role
+    event BillerUpdated(
+        address indexed biller,
+        bool is_biller
+    );
 
-        /// App pricing: app_id => billing interval in days (for subscriptions)
-        mapping(uint256 => uint256) app_interval_days;
+    /// Emitted when purchase proceeds are credited to an account's ledger balance
+    event BalanceCredited(
+        address indexed account,
+        address indexed token,
+        uint256 indexed app_id,
+        uint256 amount
+    );
 
-        /// App pricing: app_id => is active (pricing set)
-        mapping(uint256 => bool) app_pricing_active;
+    /// Emitted when an admin sets the escrow hold period for an app
+    event EscrowHoldSet(
+        uint256 indexed app_id,
+        uint256 hold_days
+    );
 
-        /// Purchase tracking: app_id => buyer => has purchased
-        mapping(uint256 => mapping(address => bool)) has_purchased;
+    /// Emitted when a purchase's developer share is escrowed for a payee
+    event EscrowCredited(
+        address indexed account,
+        uint256 indexed app_id,
+        address indexed buyer,
+        uint256 amount,
+        uint256 release_at
+    );
 
-        /// Billing payments: app_id => period_hash => amount paid
-        mapping(uint256 => mapping(uint256 => uint256)) billing_payments;
+    /// Emitted when unlocked escrow is moved to an account's ledger balance
+    event EscrowReleased(
+        address indexed account,
+        uint256 amount
+    );
 
-        /// Total platform revenue (for analytics)
-        uint256 total_platform_revenue;
+    /// Emitted when a buyer disputes an escrowed purchase
+    event DisputeOpened(
+        uint256 indexed app_id,
+        address indexed buyer,
+        uint256 developer_share
+    );
 
-        /// Total developer payouts (for analytics)
-        uint256 total_developer_payouts;
-    }
-}
+    /// Emitted when an admin resolves a dispute as a refund or a release
+    event DisputeResolved(
+        uint256 indexed app_id,
+        address indexed buyer,
+        address indexed arbitrator,
+        bool refunded,
+        uint256 developer_share
+    );
 
-#[public]
-impl VarityPayments {
-    /// Initialize contract (set deployer as owner)
-    pub fn initialize(&mut self) -> Result<(), Vec<u8>> {
-        let deployer = self.__stylus_host.msg_sender();
+    /// Emitted when a developer sets the refund window for an app
+    event RefundWindowSet(
+        uint256 indexed app_id,
+        address indexed developer,
+        uint256 window_days
+    );
 
-        // Only initialize once (check if owner is zero)
-        if self.owner.get() != Address::ZERO {
-            return Ok(()); // Already initialized
-        }
+    /// Emitted when a developer sets the free trial length for an app
+    event TrialDaysSet(
+        uint256 indexed app_id,
+        address indexed developer,
+        uint256 trial_days
+    );
 
-        self.owner.set(deployer);
-        self.total_platform_revenue.set(U256::ZERO);
-        self.total_developer_payouts.set(U256::ZERO);
+    /// Emitted when a user starts a free trial
+    event TrialStarted(
+        uint256 indexed app_id,
+        address indexed user,
+        uint256 ends_at
+    );
 
-        Ok(())
-    }
+    /// Emitted when a user who started a trial purchases the app
+    event TrialConverted(
+        uint256 indexed app_id,
+        address indexed user,
+        uint256 timestamp
+    );
 
-    // ============ Developer Functions ============
+    /// Emitted when a developer proposes a new revenue split for an app
+    event PayeesProposed(
+        uint256 indexed app_id,
+        uint256 version,
+        address[] payees,
+        uint256[] shares_bps
+    );
 
-    /// Set the price for an app (developer only)
-    ///
-    /// # Arguments
-    /// * `app_id` - The app ID from VarityAppRegistry
-    /// * `price_usdc` - Price in USDC (6 decimals, e.g., 99_000_000 = $99)
-    /// * `is_subscription` - Whether this is a subscription or one-time purchase
-    /// * `interval_days` - Billing interval for subscriptions (e.g., 30 for monthly)
-    pub fn set_app_price(
-        &mut self,
-        app_id: u64,
-        price_usdc: u64,
-        is_subscription: bool,
-        interval_days: u64,
-    ) -> Result<(), Vec<u8>> {
-        if app_id == 0 {
-            return Err(ERROR_INVALID_APP_ID.to_vec());
-        }
-        if price_usdc == 0 {
-            return Err(ERROR_INVALID_PRICE.to_vec());
-        }
-        if is_subscription && interval_days == 0 {
-            return Err(ERROR_INVALID_PRICE.to_vec());
-        }
+    /// Emitted when the owner confirms a proposed revenue split
+    event PayeesConfirmed(
+        uint256 indexed app_id,
+        uint256 version
+    );
 
-        let app_id_u256 = U256::from(app_id);
-        let caller = self.__stylus_host.msg_sender();
+    /// Emitted when an address registers as an affiliate
+    event AffiliateRegistered(
+        address indexed affiliate
+    );
 
-        // Store pricing
-        self.app_prices.setter(app_id_u256).set(U256::from(price_usdc));
-        self.app_developers.setter(app_id_u256).set(caller);
-        self.app_is_subscription.setter(app_id_u256).set(is_subscription);
-        self.app_interval_days.setter(app_id_u256).set(U256::from(interval_days));
-        self.app_pricing_active.setter(app_id_u256).set(true);
+    /// Emitted when a developer sets an app's referral commission
+    event ReferralShareSet(
+        uint256 indexed app_id,
+        address indexed developer,
+        uint256 share_bps
+    );
 
-        // Emit event
-        self.vm().log(AppPriceSet {
-            app_id: app_id_u256,
-            developer: caller,
-            price_usdc: U256::from(price_usdc),
-            is_subscription,
-            interval_days: U256::from(interval_days),
-        });
+    /// Emitted when an affiliate is credited a referral commission
+    event AffiliatePaid(
+        uint256 indexed app_id,
+        address indexed affiliate,
+        address indexed buyer,
+        uint256 amount
+    );
 
-        Ok(())
-    }
+    /// ERC-1155: licenses minted, transferred or burned
+    event TransferSingle(
+        address indexed operator,
+        address indexed from,
+        address indexed to,
+        uint256 id,
+        uint256 value
+    );
 
-    /// Update app price (developer only)
-    pub fn update_app_price(&mut self, app_id: u64, new_price_usdc: u64) -> Result<(), Vec<u8>> {
-        let app_id_u256 = U256::from(app_id);
-        let caller = self.__stylus_host.msg_sender();
+    /// ERC-1155: batch license transfer
+    event TransferBatch(
+        address indexed operator,
+        address indexed from,
+        address indexed to,
+        uint256[] ids,
+        uint256[] values
+    );
 
-        // Check pricing is active
-        if !self.app_pricing_active.get(app_id_u256) {
-            return Err(ERROR_APP_NOT_FOR_SALE.to_vec());
-        }
+    /// ERC-1155: operator approval for all of an account's licenses
+    event ApprovalForAll(
+        address indexed account,
+        address indexed operator,
+        bool approved
+    );
 
-        // Check caller is the developer
-        if self.app_developers.get(app_id_u256) != caller {
-            return Err(ERROR_UNAUTHORIZED.to_vec());
-        }
+    /// Emitted when a developer sets an app's license transferability and royalty
+    event LicenseTermsSet(
+        uint256 indexed app_id,
+        address indexed developer,
+        bool transferable,
+        uint256 royalty_bps
+    );
 
-        if new_price_usdc == 0 {
-            return Err(ERROR_INVALID_PRICE.to_vec());
-        }
+    /// Emitted when a resale royalty is paid and split 90/10
+    event RoyaltyPaid(
+        uint256 indexed app_id,
+        address indexed payer,
+        uint256 sale_price,
+        uint256 royalty,
+        uint256 developer_share,
+        uint256 platform_fee
+    );
 
-        // Update price
-        self.app_prices.setter(app_id_u256).set(U256::from(new_price_usdc));
+    /// Emitted when a developer sets an app's seat volume discounts
+    event SeatDiscountsSet(
+        uint256 indexed app_id,
+        address indexed developer,
+        uint64[] min_quantities,
+        uint64[] discount_bps
+    );
 
-        // Emit event
-        self.vm().log(AppPriceSet {
-            app_id: app_id_u256,
-            developer: caller,
-            price_usdc: U256::from(new_price_usdc),
-            is_subscription: self.app_is_subscription.get(app_id_u256),
-            interval_days: self.app_interval_days.get(app_id_u256),
-        });
+    /// Emitted when an organization buys seats
+    event SeatsPurchased(
+        uint256 indexed app_id,
+        address indexed org,
+        uint256 quantity,
+        uint256 total_amount,
+        uint256 discount_bps,
+        uint256 developer_share,
+        uint256 platform_fee
+    );
 
-        Ok(())
-    }
+    /// Emitted when an organization pays for another interval of its seats
+    event SeatsRenewed(
+        uint256 indexed app_id,
+        address indexed org,
+        uint256 quantity,
+        uint256 total_amount,
+        uint256 paid_through
+    );
 
-    /// Deactivate app pricing (developer only)
-    pub fn deactivate_app_pricing(&mut self, app_id: u64) -> Result<(), Vec<u8>> {
-        let app_id_u256 = U256::from(app_id);
-        let caller = self.__stylus_host.msg_sender();
+    /// Emitted when an organization assigns a seat to a member
+    event SeatAssigned(
+        uint256 indexed app_id,
+        address indexed org,
+        address indexed member
+    );
 
-        // Check pricing is active
-        if !self.app_pricing_active.get(app_id_u256) {
-            return Err(ERROR_APP_NOT_FOR_SALE.to_vec());
-        }
+    /// Emitted when an organization revokes a member's seat
+    event SeatRevoked(
+        uint256 indexed app_id,
+        address indexed org,
+        address indexed member
+    );
 
-        // Check caller is the developer
-        if self.app_developers.get(app_id_u256) != caller {
-            return Err(ERROR_UNAUTHORIZED.to_vec());
-        }
+    /// Emitted when the owner sets or clears a developer's fee override
+    event DeveloperFeeSet(
+        address indexed developer,
+        uint256 fee_bps,
+        bool active
+    );
 
-        self.app_pricing_active.setter(app_id_u256).set(false);
+    /// Emitted when the owner sets or clears an app's fee override
+    event AppFeeSet(
+        uint256 indexed app_id,
+        uint256 fee_bps,
+        bool active
+    );
 
-        Ok(())
-    }
+    /// Emitted when the owner replaces the volume-based fee tiers
+    event FeeTiersSet(
+        uint256[] thresholds,
+        uint64[] fee_bps
+    );
 
-    // ============ Purchase Functions ============
+    /// Emitted when a developer schedules a future price change
+    event PriceChangeScheduled(
+        uint256 indexed app_id,
+        address indexed developer,
+        uint256 old_price,
+        uint256 new_price,
+        uint256 effective_at
+    );
 
-    /// Purchase an app — 90% to developer, 10% to Varity treasury
+    /// Emitted when a developer cancels a scheduled price change
+    event PriceChangeCancelled(
+        uint256 indexed app_id,
+        uint256 new_price,
+        uint256 effective_at
+    );
+
+    /// Emitted when a developer creates or updates a coupon
+    event CouponSet(
+        uint256 indexed app_id,
+        bytes32 indexed code_hash,
+        uint256 percent_bps,
+        uint256 fixed_discount,
+        uint256 max_redemptions,
+        uint256 expires_at,
+        bool allowlist_only
+    );
+
+    /// Emitted when a developer deactivates a coupon
+    event CouponDeactivated(
+        uint256 indexed app_id,
+        bytes32 indexed code_hash
+    );
+
+    /// Emitted when a coupon is redeemed on a purchase
+    event CouponRedeemed(
+        uint256 indexed app_id,
+        bytes32 indexed code_hash,
+        address indexed buyer,
+        uint256 list_price,
+        uint256 discount,
+        uint256 redemptions
+    );
+
+    /// Emitted when a purchase is refunded (refund is credited to the payer's ledger balance)
+    event PurchaseRefunded(
+        uint256 indexed app_id,
+        address indexed buyer,
+        address indexed refunded_by,
+        uint256 total_amount,
+        uint256 developer_share,
+        uint256 platform_fee,
+        uint256 timestamp
+    );
+
+    /// Emitted when the owner grants or revokes admin rights
+    event AdminUpdated(
+        address indexed admin,
+        bool is_admin
+    );
+
+    /// Emitted when an account withdraws from its ledger balance
+    event Withdrawal(
+        address indexed account,
+        address indexed token,
+        address indexed to,
+        uint256 amount
+    );
+
+    /// Emitted when an app's transaction count for a period is reported
+    event UsageReported(
+        uint256 indexed app_id,
+        uint256 indexed period_hash,
+        address indexed reporter,
+        uint256 tx_count
+    );
+
+    /// Emitted when the owner grants or revokes the usage reporter role
+    event ReporterUpdated(
+        address indexed reporter,
+        bool is_reporter
+    );
+
+    /// Emitted when the owner sets the overage price per transaction
+    event OverageRateSet(
+        uint256 rate_per_tx
+    );
+
+    /// Emitted when a developer deposits prepaid infrastructure credit
+    event CreditDeposited(
+        address indexed developer,
+        uint256 amount,
+        uint256 balance
+    );
+
+    /// Emitted when a bill is drawn from a developer's prepaid credit
+    event CreditDrawn(
+        address indexed developer,
+        uint256 indexed app_id,
+        uint256 period_hash,
+        uint256 amount,
+        uint256 balance
+    );
+
+    /// Emitted when a developer withdraws unused prepaid credit
+    event CreditWithdrawn(
+        address indexed developer,
+        address indexed to,
+        uint256 amount,
+        uint256 balance
+    );
+
+    /// Emitted when a developer's prepaid credit falls below their threshold
+    event CreditLowBalance(
+        address indexed developer,
+        uint256 balance,
+        uint256 threshold
+    );
+}
+
+// ============ Storage ============
+
+sol_storage! {
+    #[entrypoint]
+    pub struct VarityPayments {
+        /// Contract owner
+        address owner;
+
+        /// Admin addresses (support team) allowed to issue refunds
+        mapping(address => bool) admins;
+
+        /// Billing role addresses allowed to issue invoices (admins may too)
+        mapping(address => bool) billers;
+
+        /// App pricing: app_id => price in USDC (6 decimals)
+        mapping(uint256 => uint256) app_prices;
+
+        /// Scheduled price change: app_id => new price / effective time (0 = none).
+        /// Applied lazily: current_price() reads it once due.
+        mapping(uint256 => uint256) app_pending_prices;
+        mapping(uint256 => uint256) app_pending_price_times;
+        /// Price history: app_id => index => price / effective time
+        mapping(uint256 => uint256) price_history_counts;
+        mapping(uint256 => mapping(uint256 => uint256)) price_history_prices;
+        mapping(uint256 => mapping(uint256 => uint256)) price_history_times;
+
+        /// App pricing: app_id => developer address
+        mapping(uint256 => address) app_developers;
+
+        /// App pricing: app_id => is subscription (vs one-time)
+        mapping(uint256 => bool) app_is_subscription;
+
+        /// App pricing: app_id => billing interval in days (for subscriptions)
+        mapping(uint256 => uint256) app_interval_days;
+
+        /// App pricing: app_id => is active (pricing set)
+        mapping(uint256 => bool) app_pricing_active;
+
+        /// Deactivated subscription apps: app_id => subscribers may claim a
+        /// pro-rata refund of their unused period
+        mapping(uint256 => bool) app_subscriber_refunds;
+
+        /// Subscription apps: app_id => latest paid-through time of any
+        /// subscriber (bounds how long refunds may still be owed)
+        mapping(uint256 => uint256) app_latest_paid_through;
+
+        /// App pricing: app_id => refund window in days (0 = no refunds)
+        mapping(uint256 => uint256) app_refund_window_days;
+
+        /// Purchase tracking: app_id => buyer => has purchased
+        mapping(uint256 => mapping(address => bool)) has_purchased;
+
+        /// Purchase tracking: app_id => buyer => address that paid (refunds go here)
+        mapping(uint256 => mapping(address => address)) purchase_payers;
+
+        /// Purchase tracking: app_id => buyer => purchase timestamp
+        mapping(uint256 => mapping(address => uint256)) purchase_timestamps;
+
+        /// Subscriptions: app_id => license holder => end of the paid interval
+        mapping(uint256 => mapping(address => uint256)) subscription_paid_through;
+
+        /// Purchase tracking: app_id => buyer => total amount paid
+        mapping(uint256 => mapping(address => uint256)) purchase_amounts;
+
+        /// Purchase tracking: app_id => buyer => platform fee taken
+        mapping(uint256 => mapping(address => uint256)) purchase_platform_fees;
+
+        /// Escrow: app_id => hold period in days (0 = developer shares credited immediately)
+        mapping(uint256 => uint256) app_escrow_hold_days;
+        /// app_id => buyer => day the developer share unlocks (0 = not escrowed)
+        mapping(uint256 => mapping(address => uint256)) purchase_escrow_release_days;
+        mapping(uint256 => mapping(address => bool)) purchase_disputed;
+        /// Escrowed USDC per payee: held (including unlocked but not yet
+        /// released to the ledger) and under dispute
+        mapping(address => uint256) escrow_balances;
+        mapping(address => uint256) disputed_balances;
+        /// account => day => escrowed amount unlocking that day
+        mapping(address => mapping(uint256 => uint256)) escrow_unlocks;
+
+        /// Ledger balances: account => token => amount withdrawable
+        /// (developers and the treasury are credited here on each purchase)
+        mapping(address => mapping(address => uint256)) balances;
+
+        /// Billing payments: app_id => period_hash => amount paid
+        mapping(uint256 => mapping(uint256 => uint256)) billing_payments;
+
+        /// Tier catalog (tier_id => field, IDs 0..tier_count)
+        uint256 tier_count;
+        mapping(uint256 => string) tier_names;
+        /// Monthly price in USDC (6 decimals)
+        mapping(uint256 => uint256) tier_monthly_prices;
+        /// Monthly transaction quota (0 = unmetered)
+        mapping(uint256 => uint256) tier_tx_quotas;
+        mapping(uint256 => bool) tier_is_active;
+
+        /// Tier history: app_id => index => tier_id / effective timestamp
+        /// (stored as mapping with count, ordered by effective timestamp)
+        mapping(uint256 => mapping(uint256 => uint256)) app_tier_change_tiers;
+        mapping(uint256 => mapping(uint256 => uint256)) app_tier_change_times;
+        mapping(uint256 => uint256) app_tier_change_counts;
+
+        /// Counter for next invoice ID (IDs start at 1)
+        uint256 next_invoice_id;
+
+        /// Invoices - using separate mappings for each field
+        mapping(uint256 => uint256) invoice_app_ids;
+        mapping(uint256 => address) invoice_developers;
+        mapping(uint256 => uint256) invoice_periods;
+        mapping(uint256 => uint256) invoice_tiers;
+        mapping(uint256 => uint256) invoice_amounts;
+        mapping(uint256 => uint256) invoice_amounts_paid;
+        mapping(uint256 => uint256) invoice_due_dates;
+        mapping(uint256 => uint256) invoice_issued_at;
+
+        /// Invoices per app (app_id => index => invoice_id, stored as mapping with count)
+        mapping(uint256 => mapping(uint256 => uint256)) app_invoices;
+        mapping(uint256 => uint256) app_invoice_counts;
+
+        /// Usage reporter role (metering service) addresses
+        mapping(address => bool) reporters;
+
+        /// Metering: app_id => period_hash => transactions used (cumulative)
+        mapping(uint256 => mapping(uint256 => uint256)) app_period_usage;
+
+        /// Overage price per transaction above the tier quota, in USDC (6 decimals)
+        uint256 overage_rate_per_tx;
+
+        /// Free trials: app_id => trial length in days (0 = no trial)
+        mapping(uint256 => uint256) app_trial_days;
+        /// app_id => user => trial end timestamp (non-zero once a trial was started)
+        mapping(uint256 => mapping(address => uint256)) trial_ends_at;
+        mapping(uint256 => mapping(address => bool)) trial_converted;
+        /// Trial funnel analytics
+        mapping(uint256 => uint256) app_trial_starts;
+        mapping(uint256 => uint256) app_trial_conversions;
+
+        /// Revenue splits, versioned per app (version 0 = all to the developer).
+        /// A proposal is written to version + 1 and becomes active on confirmation.
+        mapping(uint256 => uint256) app_split_versions;
+        mapping(uint256 => bool) app_split_pending;
+        /// app_id => version => payee count (0 = all to the developer)
+        mapping(uint256 => mapping(uint256 => uint256)) split_payee_counts;
+        /// app_id => version => index => payee / share in basis points
+        mapping(uint256 => mapping(uint256 => mapping(uint256 => address))) split_payees;
+        mapping(uint256 => mapping(uint256 => mapping(uint256 => uint256))) split_shares;
+        /// Split version each purchase was credited under (for refunds)
+        mapping(uint256 => mapping(address => uint256)) purchase_split_versions;
+        /// Analytics: app_id => payee => lifetime earnings (net of refunds)
+        mapping(uint256 => mapping(address => uint256)) app_payee_earnings;
+
+        /// Referrals: commission in basis points of the developer share
+        mapping(uint256 => uint256) app_referral_shares;
+        mapping(address => bool) affiliates;
+        /// Affiliate analytics (net of refunds)
+        mapping(address => uint256) affiliate_earnings;
+        mapping(address => uint256) affiliate_referral_counts;
+        /// Referrer and commission of each purchase (for refunds)
+        mapping(uint256 => mapping(address => address)) purchase_referrers;
+        mapping(uint256 => mapping(address => uint256)) purchase_referral_fees;
+
+        /// License tokens (ERC-1155, token ID = app_id): app_id => holder => balance
+        mapping(uint256 => mapping(address => uint256)) license_balances;
+        mapping(address => mapping(address => bool)) operator_approvals;
+        /// User library: holder => index => app_id of each license held, and
+        /// app_id => holder => index + 1 (0 = not held). Unordered.
+        mapping(address => uint256) library_counts;
+        mapping(address => mapping(uint256 => uint256)) library_apps;
+        mapping(uint256 => mapping(address => uint256)) library_positions;
+        /// License terms set by the developer
+        mapping(uint256 => bool) app_license_transferable;
+        mapping(uint256 => uint256) app_royalty_bps;
+
+        /// Seats: app_id => org => seats bought / seats assigned
+        mapping(uint256 => mapping(address => uint256)) org_seats;
+        mapping(uint256 => mapping(address => uint256)) org_seats_assigned;
+        /// Subscription seats: app_id => org => end of the paid interval
+        mapping(uint256 => mapping(address => uint256)) org_seats_paid_through;
+        /// app_id => member => org whose seat they hold (zero = none)
+        mapping(uint256 => mapping(address => address)) seat_orgs;
+        /// Volume discounts: app_id => index => min quantity / discount in bps
+        mapping(uint256 => uint256) seat_discount_counts;
+        mapping(uint256 => mapping(uint256 => uint256)) seat_discount_min_quantities;
+        mapping(uint256 => mapping(uint256 => uint256)) seat_discount_bps;
+
+        /// Platform fee schedule (see fee_bps()): overrides and volume tiers
+        mapping(address => bool) developer_fee_set;
+        mapping(address => uint256) developer_fee_bps;
+        mapping(uint256 => bool) app_fee_set;
+        mapping(uint256 => uint256) app_fee_bps;
+        /// Tier i applies once a developer's lifetime earnings reach its threshold
+        uint256 fee_tier_count;
+        mapping(uint256 => uint256) fee_tier_thresholds;
+        mapping(uint256 => uint256) fee_tier_bps;
+
+        /// Coupons: app_id => keccak256(code) => field
+        mapping(uint256 => mapping(bytes32 => bool)) coupon_active;
+        /// Percent discount in basis points (0 for fixed-amount coupons)
+        mapping(uint256 => mapping(bytes32 => uint256)) coupon_percent_bps;
+        /// Fixed discount in USDC (0 for percent coupons)
+        mapping(uint256 => mapping(bytes32 => uint256)) coupon_fixed_discounts;
+        /// Maximum redemptions (0 = unlimited)
+        mapping(uint256 => mapping(bytes32 => uint256)) coupon_max_redemptions;
+        mapping(uint256 => mapping(bytes32 => uint256)) coupon_redemptions;
+        /// Expiry timestamp (0 = never)
+        mapping(uint256 => mapping(bytes32 => uint256)) coupon_expires_at;
+        /// Only buyers on the coupon's allowlist may redeem it
+        mapping(uint256 => mapping(bytes32 => bool)) coupon_allowlist_only;
+        mapping(uint256 => mapping(bytes32 => mapping(address => bool))) coupon_allowlist;
+
+        /// Signed requests: signer => next nonce (replay protection)
+        mapping(address => uint256) nonces;
+
+        /// Prepaid infrastructure credit: developer => USDC held by this contract
+        mapping(address => uint256) credit_balances;
+
+        /// Prepaid credit: developer => low-balance threshold (0 = no alerts)
+        mapping(address => uint256) credit_low_thresholds;
+
+        /// Total platform revenue (for analytics)
+        uint256 total_platform_revenue;
+
+        /// Total developer payouts (for analytics)
+        uint256 total_developer_payouts;
+
+        /// Analytics: app_id => gross revenue (total paid by buyers, net of refunds)
+        mapping(uint256 => uint256) app_gross_revenue;
+
+        /// Analytics: app_id => day (timestamp / 86400) => gross revenue that day
+        mapping(uint256 => mapping(uint256 => uint256)) app_daily_revenue;
+
+        /// Analytics: developer => lifetime earnings (developer share, net of refunds)
+        mapping(address => uint256) developer_earnings;
+
+        /// Analytics: app_id => number of purchases (net of refunds)
+        mapping(uint256 => uint256) app_purchase_counts;
+
+        /// Analytics: app_id => buyers who purchased or claimed a license over
+        /// the app's lifetime (net of refunds; lapses and transfers not counted)
+        mapping(uint256 => uint256) app_lifetime_buyers;
+    }
+}
+
+#[public]
+impl VarityPayments {
+    /// Initialize contract (set deployer as owner)
+    pub fn initialize(&mut self) -> Result<(), Vec<u8>> {
+        let deployer = self.__stylus_host.msg_sender();
+
+        // Only initialize once (check if owner is zero)
+        if self.owner.get() != Address::ZERO {
+            return Ok(()); // Already initialized
+        }
+
+        self.owner.set(deployer);
+        self.total_platform_revenue.set(U256::ZERO);
+        self.total_developer_payouts.set(U256::ZERO);
+
+        self.seed_tiers();
+
+        Ok(())
+    }
+
+    /// Seed the default tier catalog on a contract deployed before it
+    /// existed (owner only, while the catalog is empty)
+    pub fn seed_default_tiers(&mut self) -> Result<(), Vec<u8>> {
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if self.tier_count.get() != U256::ZERO {
+            return Err(ERROR_TIERS_SEEDED.to_vec());
+        }
+
+        self.seed_tiers();
+
+        Ok(())
+    }
+
+    // ============ Developer Functions ============
+
+    /// List an app for sale; the caller becomes its developer
     ///
-    /// Uses ERC-20 USDC transferFrom. Buyer must approve() this contract
-    /// for the purchase amount before calling.
+    /// Pricing is set once: later price changes go through update_app_price()
+    /// or schedule_price_change(), and the billing model is fixed.
     ///
-    /// On Arbitrum One, thirdweb's payModal handles:
-    /// - Credit card → USDC acquisition
-    /// - USDC approval for this contract
-    /// - Transaction execution
-    pub fn purchase_app(&mut self, app_id: u64) -> Result<(), Vec<u8>> {
+    /// # Arguments
+    /// * `app_id` - The app ID from VarityAppRegistry
+    /// * `price_usdc` - Price in USDC (6 decimals, e.g., 99_000_000 = $99);
+    ///   0 lists a one-time app as free (claimed via claim_free_app())
+    /// * `is_subscription` - Whether this is a subscription or one-time purchase
+    /// * `interval_days` - Billing interval for subscriptions (e.g., 30 for monthly)
+    pub fn set_app_price(
+        &mut self,
+        app_id: u64,
+        price_usdc: U256,
+        is_subscription: bool,
+        interval_days: u64,
+    ) -> Result<(), Vec<u8>> {
+        if app_id == 0 {
+            return Err(ERROR_INVALID_APP_ID.to_vec());
+        }
+        if is_subscription && (price_usdc == U256::ZERO || interval_days == 0) {
+            return Err(ERROR_INVALID_PRICE.to_vec());
+        }
+
         let app_id_u256 = U256::from(app_id);
-        let buyer = self.__stylus_host.msg_sender();
+        let caller = self.__stylus_host.msg_sender();
+
+        // The first caller becomes the developer; a listed app is not repriced here
+        let developer = self.app_developers.get(app_id_u256);
+        if developer != Address::ZERO && developer != caller {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if developer != Address::ZERO {
+            return Err(ERROR_PRICING_SET.to_vec());
+        }
+
+        // Store pricing
+        self.app_developers.setter(app_id_u256).set(caller);
+        self.app_is_subscription.setter(app_id_u256).set(is_subscription);
+        self.app_interval_days.setter(app_id_u256).set(U256::from(interval_days));
+        self.app_pricing_active.setter(app_id_u256).set(true);
+        self.app_subscriber_refunds.setter(app_id_u256).set(false);
+        let now = U256::from(self.__stylus_host.block_timestamp());
+        self.schedule_price(app_id_u256, price_usdc, now)?;
+
+        // Emit event
+        self.vm().log(AppPriceSet {
+            app_id: app_id_u256,
+            developer: caller,
+            price_usdc,
+            is_subscription,
+            interval_days: U256::from(interval_days),
+        });
+
+        Ok(())
+    }
+
+    /// Update app price immediately (developer only)
+    ///
+    /// Subscription price increases need notice; use schedule_price_change().
+    /// Replaces any scheduled change.
+    pub fn update_app_price(&mut self, app_id: u64, new_price_usdc: U256) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let caller = self.__stylus_host.msg_sender();
+
+        // Check pricing is active
+        if !self.app_pricing_active.get(app_id_u256) {
+            return Err(ERROR_APP_NOT_FOR_SALE.to_vec());
+        }
+
+        // Check caller is the developer
+        if self.app_developers.get(app_id_u256) != caller {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        // Only one-time apps can be free
+        if new_price_usdc == U256::ZERO && self.app_is_subscription.get(app_id_u256) {
+            return Err(ERROR_INVALID_PRICE.to_vec());
+        }
+
+        // Update price
+        let now = U256::from(self.__stylus_host.block_timestamp());
+        self.schedule_price(app_id_u256, new_price_usdc, now)?;
+
+        // Emit event
+        self.vm().log(AppPriceSet {
+            app_id: app_id_u256,
+            developer: caller,
+            price_usdc: new_price_usdc,
+            is_subscription: self.app_is_subscription.get(app_id_u256),
+            interval_days: self.app_interval_days.get(app_id_u256),
+        });
+
+        Ok(())
+    }
+
+    /// Schedule a price change taking effect at `effective_at` (developer only)
+    ///
+    /// Subscription price increases need at least 7 days' notice. Only one
+    /// change can be pending; scheduling again replaces it.
+    pub fn schedule_price_change(&mut self, app_id: u64, new_price_usdc: U256, effective_at: u64) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let caller = self.__stylus_host.msg_sender();
+
+        // Check pricing is active
+        if !self.app_pricing_active.get(app_id_u256) {
+            return Err(ERROR_APP_NOT_FOR_SALE.to_vec());
+        }
+
+        // Check caller is the developer
+        if self.app_developers.get(app_id_u256) != caller {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        // Only one-time apps can be free
+        if new_price_usdc == U256::ZERO && self.app_is_subscription.get(app_id_u256) {
+            return Err(ERROR_INVALID_PRICE.to_vec());
+        }
+        let now = U256::from(self.__stylus_host.block_timestamp());
+        let effective_at = U256::from(effective_at);
+        if effective_at <= now {
+            return Err(ERROR_INVALID_PERIOD.to_vec());
+        }
+
+        let old_price = self.current_price(app_id_u256);
+        self.schedule_price(app_id_u256, new_price_usdc, effective_at)?;
+
+        self.vm().log(PriceChangeScheduled {
+            app_id: app_id_u256,
+            developer: caller,
+            old_price,
+            new_price: new_price_usdc,
+            effective_at,
+        });
+
+        Ok(())
+    }
+
+    /// Cancel an app's scheduled price change before it takes effect (developer only)
+    pub fn cancel_price_change(&mut self, app_id: u64) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+
+        // Check caller is the developer
+        if self.app_developers.get(app_id_u256) != self.__stylus_host.msg_sender() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        self.settle_price(app_id_u256);
+        let effective_at = self.app_pending_price_times.get(app_id_u256);
+        if effective_at == U256::ZERO {
+            return Err(ERROR_NO_PENDING_PRICE.to_vec());
+        }
+
+        let new_price = self.app_pending_prices.get(app_id_u256);
+        self.app_pending_prices.setter(app_id_u256).set(U256::ZERO);
+        self.app_pending_price_times.setter(app_id_u256).set(U256::ZERO);
+        let count = self.price_history_counts.get(app_id_u256);
+        self.price_history_counts.setter(app_id_u256).set(checked_sub(count, U256::from(1))?);
+
+        self.vm().log(PriceChangeCancelled {
+            app_id: app_id_u256,
+            new_price,
+            effective_at,
+        });
+
+        Ok(())
+    }
+
+    /// Deactivate app pricing (developer only)
+    ///
+    /// Existing licenses are kept. Subscribers keep access until the end of
+    /// their paid interval; with `refund_subscribers` they may instead claim a
+    /// pro-rata refund of the unused time via claim_subscription_refund().
+    pub fn deactivate_app_pricing(&mut self, app_id: u64, refund_subscribers: bool) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let caller = self.__stylus_host.msg_sender();
+
+        // Check pricing is active
+        if !self.app_pricing_active.get(app_id_u256) {
+            return Err(ERROR_APP_NOT_FOR_SALE.to_vec());
+        }
+
+        // Check caller is the developer
+        if self.app_developers.get(app_id_u256) != caller {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        self.app_pricing_active.setter(app_id_u256).set(false);
+        let refund_subscribers = refund_subscribers && self.app_is_subscription.get(app_id_u256);
+        self.app_subscriber_refunds.setter(app_id_u256).set(refund_subscribers);
+
+        self.vm().log(AppPricingDeactivated {
+            app_id: app_id_u256,
+            developer: caller,
+            refund_subscribers,
+        });
+
+        Ok(())
+    }
+
+    /// Resume selling a deactivated app with its pricing unchanged (developer only)
+    ///
+    /// If subscribers were offered refunds on deactivation, this waits until
+    /// every paid interval has ended so no owed refund is withdrawn.
+    pub fn reactivate_app_pricing(&mut self, app_id: u64) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let caller = self.__stylus_host.msg_sender();
+
+        // Check caller is the developer
+        if self.app_developers.get(app_id_u256) != caller {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        if self.app_pricing_active.get(app_id_u256) {
+            return Err(ERROR_APP_ACTIVE.to_vec());
+        }
+
+        // Refunds offered on deactivation stay claimable until every paid
+        // interval has ended
+        let now = U256::from(self.__stylus_host.block_timestamp());
+        if self.app_subscriber_refunds.get(app_id_u256) && now < self.app_latest_paid_through.get(app_id_u256) {
+            return Err(ERROR_REFUNDS_OUTSTANDING.to_vec());
+        }
+
+        self.app_pricing_active.setter(app_id_u256).set(true);
+        self.app_subscriber_refunds.setter(app_id_u256).set(false);
+
+        self.vm().log(AppPricingReactivated {
+            app_id: app_id_u256,
+            developer: caller,
+            price_usdc: self.current_price(app_id_u256),
+        });
+
+        Ok(())
+    }
+
+    /// Set the refund window for an app (developer only)
+    ///
+    /// Purchases can be refunded by the developer or an admin for
+    /// `window_days` after the purchase. A window of 0 disables refunds.
+    pub fn set_refund_window(&mut self, app_id: u64, window_days: u64) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let caller = self.__stylus_host.msg_sender();
+
+        // Check caller is the developer
+        if self.app_developers.get(app_id_u256) != caller {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        self.app_refund_window_days.setter(app_id_u256).set(U256::from(window_days));
+
+        self.vm().log(RefundWindowSet {
+            app_id: app_id_u256,
+            developer: caller,
+            window_days: U256::from(window_days),
+        });
+
+        Ok(())
+    }
+
+    /// Set the free trial length for a subscription app (developer only)
+    ///
+    /// A length of 0 stops new trials; running trials keep their end time.
+    pub fn set_trial_days(&mut self, app_id: u64, trial_days: u64) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let caller = self.__stylus_host.msg_sender();
+
+        // Check caller is the developer
+        if self.app_developers.get(app_id_u256) != caller {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if !self.app_is_subscription.get(app_id_u256) {
+            return Err(ERROR_NOT_SUBSCRIPTION.to_vec());
+        }
+
+        self.app_trial_days.setter(app_id_u256).set(U256::from(trial_days));
+
+        self.vm().log(TrialDaysSet {
+            app_id: app_id_u256,
+            developer: caller,
+            trial_days: U256::from(trial_days),
+        });
+
+        Ok(())
+    }
+
+    /// Set an app's seat volume discounts (developer only)
+    ///
+    /// Tiers are listed by ascending `min_quantities`; a seat purchase gets
+    /// the discount of the largest tier its quantity reaches. Empty lists
+    /// remove all discounts.
+    pub fn set_seat_discounts(
+        &mut self,
+        app_id: u64,
+        min_quantities: Vec<u64>,
+        discount_bps: Vec<u64>,
+    ) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let developer = self.__stylus_host.msg_sender();
+        if self.app_developers.get(app_id_u256) != developer {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if min_quantities.len() != discount_bps.len() || min_quantities.len() > MAX_SEAT_DISCOUNTS {
+            return Err(ERROR_INVALID_DISCOUNTS.to_vec());
+        }
+        if min_quantities.windows(2).any(|pair| pair[0] >= pair[1])
+            || discount_bps.iter().any(|bps| *bps >= BPS_DENOMINATOR)
+        {
+            return Err(ERROR_INVALID_DISCOUNTS.to_vec());
+        }
+
+        for (i, (min_quantity, bps)) in min_quantities.iter().zip(&discount_bps).enumerate() {
+            self.seat_discount_min_quantities.setter(app_id_u256).setter(U256::from(i)).set(U256::from(*min_quantity));
+            self.seat_discount_bps.setter(app_id_u256).setter(U256::from(i)).set(U256::from(*bps));
+        }
+        self.seat_discount_counts.setter(app_id_u256).set(U256::from(min_quantities.len()));
+
+        self.vm().log(SeatDiscountsSet {
+            app_id: app_id_u256,
+            developer,
+            min_quantities,
+            discount_bps,
+        });
+
+        Ok(())
+    }
+
+    // ============ Purchase Functions ============
+
+    /// Register the caller as an affiliate, eligible to earn referral commissions
+    pub fn register_affiliate(&mut self) -> Result<(), Vec<u8>> {
+        let affiliate = self.__stylus_host.msg_sender();
+        self.affiliates.setter(affiliate).set(true);
+
+        self.vm().log(AffiliateRegistered { affiliate });
+
+        Ok(())
+    }
+
+    /// Set an app's referral commission in basis points of the developer
+    /// share (developer only, max 50%; 0 disables referrals)
+    pub fn set_referral_share(&mut self, app_id: u64, share_bps: u64) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let developer = self.__stylus_host.msg_sender();
+        if self.app_developers.get(app_id_u256) != developer {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if share_bps > MAX_REFERRAL_BPS {
+            return Err(ERROR_INVALID_REFERRAL_SHARE.to_vec());
+        }
+
+        self.app_referral_shares.setter(app_id_u256).set(U256::from(share_bps));
+
+        self.vm().log(ReferralShareSet {
+            app_id: app_id_u256,
+            developer,
+            share_bps: U256::from(share_bps),
+        });
+
+        Ok(())
+    }
+
+    /// Propose a revenue split for an app (developer only)
+    ///
+    /// Shares are basis points of the developer share (after the platform
+    /// fee) and must sum to 10,000. An empty list proposes paying everything
+    /// to the developer again. The split applies to purchases made after the
+    /// owner calls confirm_payees(); a new proposal replaces a pending one.
+    pub fn propose_payees(
+        &mut self,
+        app_id: u64,
+        payees: Vec<Address>,
+        shares_bps: Vec<u64>,
+    ) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        if self.app_developers.get(app_id_u256) != self.__stylus_host.msg_sender() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if payees.len() != shares_bps.len() || payees.len() > MAX_PAYEES {
+            return Err(ERROR_INVALID_SPLIT.to_vec());
+        }
+        // Bounding each share first keeps the sum from overflowing
+        if payees.contains(&Address::ZERO)
+            || shares_bps.iter().any(|bps| *bps == 0 || *bps > BPS_DENOMINATOR)
+        {
+            return Err(ERROR_INVALID_SPLIT.to_vec());
+        }
+        if !payees.is_empty() && shares_bps.iter().sum::<u64>() != BPS_DENOMINATOR {
+            return Err(ERROR_INVALID_SPLIT.to_vec());
+        }
+
+        let version = checked_add(self.app_split_versions.get(app_id_u256), U256::from(1))?;
+        for (i, (payee, share)) in payees.iter().zip(&shares_bps).enumerate() {
+            self.split_payees.setter(app_id_u256).setter(version).setter(U256::from(i)).set(*payee);
+            self.split_shares.setter(app_id_u256).setter(version).setter(U256::from(i)).set(U256::from(*share));
+        }
+        self.split_payee_counts.setter(app_id_u256).setter(version).set(U256::from(payees.len()));
+        self.app_split_pending.setter(app_id_u256).set(true);
+
+        self.vm().log(PayeesProposed {
+            app_id: app_id_u256,
+            version,
+            payees,
+            shares_bps: shares_bps.into_iter().map(U256::from).collect(),
+        });
+
+        Ok(())
+    }
+
+    /// Create or update a coupon for an app (developer only)
+    ///
+    /// Coupons are identified by `code_hash` = keccak256(code). Exactly one
+    /// of `percent_bps` (1..=10000) and `fixed_discount` must be set.
+    /// Updating a coupon keeps its redemption count and allowlist.
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_coupon(
+        &mut self,
+        app_id: u64,
+        code_hash: B256,
+        percent_bps: u64,
+        fixed_discount: U256,
+        max_redemptions: u64,
+        expires_at: u64,
+        allowlist_only: bool,
+    ) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        if self.app_developers.get(app_id_u256) != self.__stylus_host.msg_sender() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if code_hash == B256::ZERO
+            || percent_bps > BPS_DENOMINATOR
+            || (percent_bps == 0) == (fixed_discount == U256::ZERO)
+        {
+            return Err(ERROR_INVALID_COUPON.to_vec());
+        }
+
+        self.coupon_active.setter(app_id_u256).setter(code_hash).set(true);
+        self.coupon_percent_bps.setter(app_id_u256).setter(code_hash).set(U256::from(percent_bps));
+        self.coupon_fixed_discounts.setter(app_id_u256).setter(code_hash).set(fixed_discount);
+        self.coupon_max_redemptions.setter(app_id_u256).setter(code_hash).set(U256::from(max_redemptions));
+        self.coupon_expires_at.setter(app_id_u256).setter(code_hash).set(U256::from(expires_at));
+        self.coupon_allowlist_only.setter(app_id_u256).setter(code_hash).set(allowlist_only);
+
+        self.vm().log(CouponSet {
+            app_id: app_id_u256,
+            code_hash,
+            percent_bps: U256::from(percent_bps),
+            fixed_discount,
+            max_redemptions: U256::from(max_redemptions),
+            expires_at: U256::from(expires_at),
+            allowlist_only,
+        });
+
+        Ok(())
+    }
+
+    /// Add or remove buyers from a coupon's allowlist (developer only)
+    pub fn set_coupon_allowlist(
+        &mut self,
+        app_id: u64,
+        code_hash: B256,
+        buyers: Vec<Address>,
+        allowed: bool,
+    ) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        if self.app_developers.get(app_id_u256) != self.__stylus_host.msg_sender() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        for buyer in buyers {
+            self.coupon_allowlist.setter(app_id_u256).setter(code_hash).setter(buyer).set(allowed);
+        }
+
+        Ok(())
+    }
+
+    /// Deactivate a coupon (developer only)
+    pub fn deactivate_coupon(&mut self, app_id: u64, code_hash: B256) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        if self.app_developers.get(app_id_u256) != self.__stylus_host.msg_sender() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if !self.coupon_active.getter(app_id_u256).get(code_hash) {
+            return Err(ERROR_INVALID_COUPON.to_vec());
+        }
+
+        self.coupon_active.setter(app_id_u256).setter(code_hash).set(false);
+
+        self.vm().log(CouponDeactivated {
+            app_id: app_id_u256,
+            code_hash,
+        });
+
+        Ok(())
+    }
+
+    /// Purchase an app — 90% to developer, 10% to Varity treasury
+    ///
+    /// Fails if the current price is above `max_price`, so a buyer is never
+    /// charged more than the price they were quoted.
+    ///
+    /// Uses ERC-20 USDC transferFrom. Buyer must approve() this contract
+    /// for the purchase amount before calling. The full price is pulled
+    /// into this contract in one transfer and the split is credited to the
+    /// developer and treasury ledger balances, so a recipient that cannot
+    /// receive USDC never blocks a purchase. If the pull fails, the purchase
+    /// state is rolled back.
+    ///
+    /// On Arbitrum One, thirdweb's payModal handles:
+    /// - Credit card → USDC acquisition
+    /// - USDC approval for this contract
+    /// - Transaction execution
+    pub fn purchase_app(&mut self, app_id: u64, max_price: U256) -> Result<(), Vec<u8>> {
+        let buyer = self.__stylus_host.msg_sender();
+        self.process_purchase(app_id, buyer, buyer, B256::ZERO, Address::ZERO, max_price)
+    }
+
+    /// Purchase an app on behalf of another address
+    ///
+    /// The caller pays (and must have approved this contract) while
+    /// `recipient` receives access. Used for gifts, team seats, and custodial
+    /// checkout flows where the paying wallet is not the end user.
+    pub fn purchase_app_for(&mut self, app_id: u64, recipient: Address, max_price: U256) -> Result<(), Vec<u8>> {
+        if recipient == Address::ZERO {
+            return Err(ERROR_INVALID_RECIPIENT.to_vec());
+        }
+
+        let payer = self.__stylus_host.msg_sender();
+        self.process_purchase(app_id, payer, recipient, B256::ZERO, Address::ZERO, max_price)
+    }
+
+    /// Purchase an app referred by a registered affiliate
+    ///
+    /// The buyer pays the normal price. The app's referral commission is
+    /// taken from the developer share and credited to the affiliate's ledger
+    /// balance; with no commission set, this is a plain purchase.
+    pub fn purchase_app_with_referrer(&mut self, app_id: u64, referrer: Address, max_price: U256) -> Result<(), Vec<u8>> {
+        let buyer = self.__stylus_host.msg_sender();
+        if !self.affiliates.get(referrer) || referrer == buyer {
+            return Err(ERROR_INVALID_REFERRER.to_vec());
+        }
+
+        self.process_purchase(app_id, buyer, buyer, B256::ZERO, referrer, max_price)
+    }
+
+    /// Purchase an app with a coupon code
+    ///
+    /// The 90/10 split is computed on the discounted price, which is what
+    /// the buyer must approve; `max_price` applies to the discounted price.
+    pub fn purchase_app_with_coupon(&mut self, app_id: u64, code: String, max_price: U256) -> Result<(), Vec<u8>> {
+        let buyer = self.__stylus_host.msg_sender();
+        self.process_purchase(app_id, buyer, buyer, keccak(code.as_bytes()), Address::ZERO, max_price)
+    }
+
+    /// Purchase an app using an EIP-2612 USDC permit instead of approve()
+    ///
+    /// The buyer signs a permit for this contract for `max_price`, so the
+    /// signature stays valid if the price changes before execution; only the
+    /// current price is pulled. If the permit call fails (e.g., it was
+    /// front-run and already used), the purchase still goes through when the
+    /// allowance is sufficient.
+    pub fn purchase_app_with_permit(
+        &mut self,
+        app_id: u64,
+        max_price: U256,
+        deadline: U256,
+        v: u8,
+        r: B256,
+        s: B256,
+    ) -> Result<(), Vec<u8>> {
+        let buyer = self.__stylus_host.msg_sender();
+        self.usdc_permit(buyer, max_price, deadline, v, r, s)?;
+        self.process_purchase(app_id, buyer, buyer, B256::ZERO, Address::ZERO, max_price)
+    }
+
+    /// Purchase an app for `buyer` from an EIP-712 signed request (anyone may relay)
+    ///
+    /// `buyer` signs PurchaseApp(app_id, buyer, max_price, nonce, deadline)
+    /// for this contract's domain ("VarityPayments", version "1"); the
+    /// relayer pays gas and `buyer` pays USDC as if they had called
+    /// purchase_app(), so a price raised after signing fails the purchase. The
+    /// USDC allowance can come from a permit the relayer submits to USDC
+    /// first, so the buyer never needs ETH.
+    #[allow(clippy::too_many_arguments)]
+    pub fn purchase_app_by_sig(
+        &mut self,
+        app_id: u64,
+        buyer: Address,
+        max_price: U256,
+        deadline: U256,
+        v: u8,
+        r: B256,
+        s: B256,
+    ) -> Result<(), Vec<u8>> {
+        let digest = self.purchase_app_digest(app_id, buyer, max_price, self.nonces.get(buyer), deadline);
+        self.use_signature(buyer, digest, deadline, v, r, s)?;
+        self.process_purchase(app_id, buyer, buyer, B256::ZERO, Address::ZERO, max_price)
+    }
+
+    /// Claim a free app (listed at a price of zero) for the caller
+    ///
+    /// Records the entitlement the same way as a paid purchase (purchase
+    /// record and license token), with no USDC transfer.
+    pub fn claim_free_app(&mut self, app_id: u64) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let user = self.__stylus_host.msg_sender();
+
+        // Check pricing is active
+        if !self.app_pricing_active.get(app_id_u256) {
+            return Err(ERROR_APP_NOT_FOR_SALE.to_vec());
+        }
+        if self.current_price(app_id_u256) != U256::ZERO {
+            return Err(ERROR_APP_NOT_FREE.to_vec());
+        }
+
+        // Ownership follows the license, so a buyer who transferred theirs
+        // can claim again
+        if self.license_balances.getter(app_id_u256).get(user) > U256::ZERO {
+            return Err(ERROR_ALREADY_PURCHASED.to_vec());
+        }
+        if self.purchase_disputed.getter(app_id_u256).get(user) {
+            return Err(ERROR_ALREADY_DISPUTED.to_vec());
+        }
+
+        let now = U256::from(self.__stylus_host.block_timestamp());
+        self.record_purchase(app_id_u256, user, user, U256::ZERO, U256::ZERO, now)?;
+
+        self.vm().log(TransferSingle {
+            operator: user,
+            from: Address::ZERO,
+            to: user,
+            id: app_id_u256,
+            value: U256::from(1),
+        });
+        self.vm().log(AppClaimed {
+            app_id: app_id_u256,
+            user,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Renew the caller's subscription for one more interval at the current price
+    ///
+    /// Extends access from the end of the paid interval (or from now, if it
+    /// has lapsed). Fails if the current price is above `max_price`. The
+    /// payment is split 90/10 like a purchase.
+    pub fn renew_subscription(&mut self, app_id: u64, max_price: U256) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let subscriber = self.__stylus_host.msg_sender();
+
+        if !self.app_pricing_active.get(app_id_u256) {
+            return Err(ERROR_APP_NOT_FOR_SALE.to_vec());
+        }
+        if !self.app_is_subscription.get(app_id_u256) {
+            return Err(ERROR_NOT_SUBSCRIPTION.to_vec());
+        }
+        if self.license_balances.getter(app_id_u256).get(subscriber) == U256::ZERO {
+            return Err(ERROR_NOT_PURCHASED.to_vec());
+        }
+        let price = self.current_price(app_id_u256);
+        if price > max_price {
+            return Err(ERROR_PRICE_ABOVE_MAX.to_vec());
+        }
+
+        // Pull first: nothing is credited unless the payment arrived
+        let contract_addr = self.vm().contract_address();
+        self.usdc_transfer_from(subscriber, contract_addr, price)?;
+
+        let now = U256::from(self.__stylus_host.block_timestamp());
+        let interval = checked_mul(self.app_interval_days.get(app_id_u256), U256::from(SECONDS_PER_DAY))?;
+        let paid_through = checked_add(self.subscription_ends_at(app_id_u256, subscriber)?.max(now), interval)?;
+        self.set_paid_through(app_id_u256, subscriber, paid_through);
+
+        self.credit_proceeds(app_id_u256, subscriber, price)?;
+        self.record_renewal_revenue(app_id_u256, price)?;
+
+        self.vm().log(SubscriptionRenewed {
+            app_id: app_id_u256,
+            subscriber,
+            amount: price,
+            paid_through,
+        });
+
+        Ok(())
+    }
+
+    /// Buy `quantity` seats of an app for the caller's organization
+    ///
+    /// Charges price * quantity less the app's volume discount, split 90/10
+    /// like a purchase. Seats accumulate across purchases and are handed out
+    /// with assign_seat(). Seats of a subscription app are paid for one
+    /// interval; seats added while the org's seats are paid up end with
+    /// them and are charged pro rata. Lapsed seats must be renewed with
+    /// renew_seats() before adding more. Fails if the total (after any
+    /// proration) is above `max_total`.
+    pub fn purchase_seats(&mut self, app_id: u64, quantity: u64, max_total: U256) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let org = self.__stylus_host.msg_sender();
+
+        if !self.app_pricing_active.get(app_id_u256) {
+            return Err(ERROR_APP_NOT_FOR_SALE.to_vec());
+        }
+        if quantity == 0 {
+            return Err(ERROR_INVALID_QUANTITY.to_vec());
+        }
+        // Free apps are claimed per user, not sold as seats
+        if self.current_price(app_id_u256) == U256::ZERO {
+            return Err(ERROR_APP_NOT_FOR_SALE.to_vec());
+        }
+
+        let discount_bps = self.seat_discount(app_id_u256, quantity)?;
+        let mut total_amount = self.seat_price(app_id_u256, quantity)?;
+        let seats = self.org_seats.getter(app_id_u256).get(org);
+        if self.app_is_subscription.get(app_id_u256) {
+            let now = U256::from(self.__stylus_host.block_timestamp());
+            let interval = checked_mul(self.app_interval_days.get(app_id_u256), U256::from(SECONDS_PER_DAY))?;
+            let paid_through = self.org_seats_paid_through.getter(app_id_u256).get(org);
+            if now < paid_through {
+                total_amount = checked_mul(total_amount, paid_through - now)? / interval;
+            } else if seats > U256::ZERO {
+                return Err(ERROR_SEATS_EXPIRED.to_vec());
+            } else {
+                self.org_seats_paid_through.setter(app_id_u256).setter(org).set(checked_add(now, interval)?);
+            }
+        }
+        if total_amount > max_total {
+            return Err(ERROR_PRICE_ABOVE_MAX.to_vec());
+        }
+
+        // Pull first: nothing is credited unless the payment arrived
+        let contract_addr = self.vm().contract_address();
+        self.usdc_transfer_from(org, contract_addr, total_amount)?;
+
+        self.org_seats.setter(app_id_u256).setter(org).set(checked_add(seats, U256::from(quantity))?);
+
+        let (developer_share, platform_fee) = self.credit_proceeds(app_id_u256, org, total_amount)?;
+
+        // Update sale analytics
+        let day = U256::from(self.__stylus_host.block_timestamp() / SECONDS_PER_DAY);
+        let gross = self.app_gross_revenue.get(app_id_u256);
+        self.app_gross_revenue.setter(app_id_u256).set(checked_add(gross, total_amount)?);
+        let daily = self.app_daily_revenue.getter(app_id_u256).get(day);
+        self.app_daily_revenue.setter(app_id_u256).setter(day).set(checked_add(daily, total_amount)?);
+        let purchases = self.app_purchase_counts.get(app_id_u256);
+        self.app_purchase_counts.setter(app_id_u256).set(checked_add(purchases, U256::from(1))?);
+
+        self.vm().log(SeatsPurchased {
+            app_id: app_id_u256,
+            org,
+            quantity: U256::from(quantity),
+            total_amount,
+            discount_bps: U256::from(discount_bps),
+            developer_share,
+            platform_fee,
+        });
+
+        Ok(())
+    }
+
+    /// Renew all of the caller's seats of a subscription app for one more
+    /// interval at the current seat price
+    ///
+    /// Extends from the end of the paid interval (or from now, if it has
+    /// lapsed). Fails if the total is above `max_total`.
+    pub fn renew_seats(&mut self, app_id: u64, max_total: U256) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let org = self.__stylus_host.msg_sender();
+
+        if !self.app_pricing_active.get(app_id_u256) {
+            return Err(ERROR_APP_NOT_FOR_SALE.to_vec());
+        }
+        if !self.app_is_subscription.get(app_id_u256) {
+            return Err(ERROR_NOT_SUBSCRIPTION.to_vec());
+        }
+        let seats = self.org_seats.getter(app_id_u256).get(org);
+        if seats == U256::ZERO {
+            return Err(ERROR_INVALID_QUANTITY.to_vec());
+        }
+        let total_amount = self.seat_price(app_id_u256, to_u64(seats)?)?;
+        if total_amount > max_total {
+            return Err(ERROR_PRICE_ABOVE_MAX.to_vec());
+        }
+
+        // Pull first: nothing is credited unless the payment arrived
+        let contract_addr = self.vm().contract_address();
+        self.usdc_transfer_from(org, contract_addr, total_amount)?;
+
+        let now = U256::from(self.__stylus_host.block_timestamp());
+        let interval = checked_mul(self.app_interval_days.get(app_id_u256), U256::from(SECONDS_PER_DAY))?;
+        let paid_through = self.org_seats_paid_through.getter(app_id_u256).get(org).max(now);
+        let paid_through = checked_add(paid_through, interval)?;
+        self.org_seats_paid_through.setter(app_id_u256).setter(org).set(paid_through);
+
+        self.credit_proceeds(app_id_u256, org, total_amount)?;
+        self.record_renewal_revenue(app_id_u256, total_amount)?;
+
+        self.vm().log(SeatsRenewed {
+            app_id: app_id_u256,
+            org,
+            quantity: seats,
+            total_amount,
+            paid_through,
+        });
+
+        Ok(())
+    }
+
+    /// Assign one of the caller's seats to `member`
+    pub fn assign_seat(&mut self, app_id: u64, member: Address) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let org = self.__stylus_host.msg_sender();
+
+        if member == Address::ZERO {
+            return Err(ERROR_INVALID_RECIPIENT.to_vec());
+        }
+        if self.seat_orgs.getter(app_id_u256).get(member) != Address::ZERO {
+            return Err(ERROR_SEAT_ASSIGNED.to_vec());
+        }
+        let assigned = self.org_seats_assigned.getter(app_id_u256).get(org);
+        if assigned >= self.org_seats.getter(app_id_u256).get(org) {
+            return Err(ERROR_NO_FREE_SEATS.to_vec());
+        }
+        if !self.seats_paid(app_id_u256, org) {
+            return Err(ERROR_SEATS_EXPIRED.to_vec());
+        }
+
+        self.seat_orgs.setter(app_id_u256).setter(member).set(org);
+        self.org_seats_assigned.setter(app_id_u256).setter(org).set(assigned + U256::from(1));
+
+        self.vm().log(SeatAssigned {
+            app_id: app_id_u256,
+            org,
+            member,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a seat the caller assigned to `member`, freeing it for reassignment
+    pub fn revoke_seat(&mut self, app_id: u64, member: Address) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let org = self.__stylus_host.msg_sender();
+
+        if self.seat_orgs.getter(app_id_u256).get(member) != org || org == Address::ZERO {
+            return Err(ERROR_SEAT_NOT_ASSIGNED.to_vec());
+        }
+
+        self.seat_orgs.setter(app_id_u256).setter(member).set(Address::ZERO);
+        let assigned = self.org_seats_assigned.getter(app_id_u256).get(org);
+        self.org_seats_assigned.setter(app_id_u256).setter(org).set(checked_sub(assigned, U256::from(1))?);
+
+        self.vm().log(SeatRevoked {
+            app_id: app_id_u256,
+            org,
+            member,
+        });
+
+        Ok(())
+    }
+
+    /// Start a free trial of a subscription app (one per address per app)
+    ///
+    /// has_access() returns true until the trial ends. Purchasing the app
+    /// (during or after the trial) converts it.
+    pub fn start_trial(&mut self, app_id: u64) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let user = self.__stylus_host.msg_sender();
+
+        let trial_days = self.app_trial_days.get(app_id_u256);
+        if !self.app_pricing_active.get(app_id_u256) || trial_days == U256::ZERO {
+            return Err(ERROR_TRIAL_UNAVAILABLE.to_vec());
+        }
+        if self.license_balances.getter(app_id_u256).get(user) > U256::ZERO {
+            return Err(ERROR_ALREADY_PURCHASED.to_vec());
+        }
+        if self.trial_ends_at.getter(app_id_u256).get(user) != U256::ZERO {
+            return Err(ERROR_TRIAL_USED.to_vec());
+        }
+
+        let now = U256::from(self.__stylus_host.block_timestamp());
+        let ends_at = checked_add(now, checked_mul(trial_days, U256::from(SECONDS_PER_DAY))?)?;
+        self.trial_ends_at.setter(app_id_u256).setter(user).set(ends_at);
+        let starts = self.app_trial_starts.get(app_id_u256);
+        self.app_trial_starts.setter(app_id_u256).set(checked_add(starts, U256::from(1))?);
+
+        self.vm().log(TrialStarted {
+            app_id: app_id_u256,
+            user,
+            ends_at,
+        });
+
+        Ok(())
+    }
+
+    /// Refund a purchase (developer or admin, within the app's refund window)
+    ///
+    /// Claws back the developer share from the developer's ledger balance (or
+    /// escrow) and the platform fee from the treasury's ledger balance,
+    /// credits the full amount to the payer's ledger balance (withdrawable via
+    /// withdraw()), and revokes the buyer's access.
+    pub fn refund_purchase(&mut self, app_id: u64, buyer: Address) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let caller = self.__stylus_host.msg_sender();
+        let developer = self.app_developers.get(app_id_u256);
+
+        // Check caller is the developer or an admin
+        if caller != developer && !self.is_admin_or_owner(caller) {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        let amount = self.purchase_amounts.getter(app_id_u256).get(buyer);
+        if !self.has_purchased.getter(app_id_u256).get(buyer) || amount == U256::ZERO {
+            return Err(ERROR_NOT_PURCHASED.to_vec());
+        }
+
+        // Check refund window
+        let purchased_at = self.purchase_timestamps.getter(app_id_u256).get(buyer);
+        let window = checked_mul(self.app_refund_window_days.get(app_id_u256), U256::from(SECONDS_PER_DAY))?;
+        let now = U256::from(self.__stylus_host.block_timestamp());
+        if window == U256::ZERO || now > checked_add(purchased_at, window)? {
+            return Err(ERROR_REFUND_WINDOW_CLOSED.to_vec());
+        }
+
+        self.refund(app_id_u256, buyer, caller)
+    }
+
+    /// Claim a pro-rata refund of the unused part of a subscription to an app
+    /// whose developer stopped selling it with subscriber refunds
+    ///
+    /// The unused part of the price is clawed back from the developer
+    /// payees, treasury and any referrer in the proportions the purchase was
+    /// split, credited to the payer's ledger balance, and the caller's
+    /// subscription ends.
+    pub fn claim_subscription_refund(&mut self, app_id: u64) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let subscriber = self.__stylus_host.msg_sender();
+
+        if self.app_pricing_active.get(app_id_u256) || !self.app_subscriber_refunds.get(app_id_u256) {
+            return Err(ERROR_REFUND_UNAVAILABLE.to_vec());
+        }
+        if !self.has_purchased.getter(app_id_u256).get(subscriber) {
+            return Err(ERROR_NOT_PURCHASED.to_vec());
+        }
+        if self.license_balances.getter(app_id_u256).get(subscriber) == U256::ZERO {
+            return Err(ERROR_LICENSE_TRANSFERRED.to_vec());
+        }
+        if self.purchase_disputed.getter(app_id_u256).get(subscriber) {
+            return Err(ERROR_ALREADY_DISPUTED.to_vec());
+        }
+
+        let (unused, interval) = self.subscription_unused_time(app_id_u256, subscriber)?;
+        if unused == U256::ZERO {
+            return Err(ERROR_REFUND_UNAVAILABLE.to_vec());
+        }
+
+        // End the subscription, clear the purchase record and reverse the
+        // unused part of the sale; the rest of an escrowed share unlocks as
+        // scheduled
+        self.check_clawback(app_id_u256, subscriber, unused, interval)?;
+        let (payer, amount) = self.clear_purchase(app_id_u256, subscriber, unused, interval)?;
+
+        // Credit the refund to the payer
+        let usdc_addr = Address::from_slice(&USDC_ADDRESS);
+        let payer_balance = self.balances.getter(payer).get(usdc_addr);
+        self.balances.setter(payer).setter(usdc_addr).set(checked_add(payer_balance, amount)?);
+
+        self.vm().log(BalanceCredited {
+            account: payer,
+            token: usdc_addr,
+            app_id: app_id_u256,
+            amount,
+        });
+        self.vm().log(TransferSingle {
+            operator: subscriber,
+            from: subscriber,
+            to: Address::ZERO,
+            id: app_id_u256,
+            value: U256::from(1),
+        });
+        self.vm().log(SubscriptionRefunded {
+            app_id: app_id_u256,
+            subscriber,
+            amount,
+            timestamp: U256::from(self.__stylus_host.block_timestamp()),
+        });
+
+        Ok(())
+    }
+
+    // ============ Escrow & Disputes ============
+
+    /// Dispute an escrowed purchase before its developer share unlocks (buyer only)
+    ///
+    /// Freezes the developer share until an admin calls resolve_dispute().
+    pub fn open_dispute(&mut self, app_id: u64) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let buyer = self.__stylus_host.msg_sender();
+
+        let release_day = self.purchase_escrow_release_days.getter(app_id_u256).get(buyer);
+        if release_day == U256::ZERO {
+            return Err(ERROR_NOT_ESCROWED.to_vec());
+        }
+        if self.purchase_disputed.getter(app_id_u256).get(buyer) {
+            return Err(ERROR_ALREADY_DISPUTED.to_vec());
+        }
+        let today = U256::from(self.__stylus_host.block_timestamp()) / U256::from(SECONDS_PER_DAY);
+        if today >= release_day {
+            return Err(ERROR_DISPUTE_WINDOW_CLOSED.to_vec());
+        }
+
+        // Move each payee's share from held escrow to disputed
+        let (developer_share, payees, shares) = self.purchase_developer_shares(app_id_u256, buyer)?;
+        for (payee, share) in payees.into_iter().zip(shares) {
+            let held = self.escrow_balances.get(payee);
+            self.escrow_balances.setter(payee).set(checked_sub(held, share)?);
+            let unlocking = self.escrow_unlocks.getter(payee).get(release_day);
+            self.escrow_unlocks.setter(payee).setter(release_day).set(checked_sub(unlocking, share)?);
+            let disputed = self.disputed_balances.get(payee);
+            self.disputed_balances.setter(payee).set(checked_add(disputed, share)?);
+        }
+        self.purchase_disputed.setter(app_id_u256).setter(buyer).set(true);
+
+        self.vm().log(DisputeOpened {
+            app_id: app_id_u256,
+            buyer,
+            developer_share,
+        });
+
+        Ok(())
+    }
+
+    /// Resolve a dispute (admin only)
+    ///
+    /// `refund` refunds the purchase to the payer as refund_purchase() would
+    /// (regardless of the refund window); otherwise the developer share is
+    /// released to the payees' ledger balances immediately.
+    pub fn resolve_dispute(&mut self, app_id: u64, buyer: Address, refund: bool) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let caller = self.__stylus_host.msg_sender();
+        if !self.is_admin_or_owner(caller) {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if !self.purchase_disputed.getter(app_id_u256).get(buyer) {
+            return Err(ERROR_NOT_DISPUTED.to_vec());
+        }
+
+        let (developer_share, payees, shares) = self.purchase_developer_shares(app_id_u256, buyer)?;
+        if refund {
+            self.refund(app_id_u256, buyer, caller)?;
+        } else {
+            let usdc_addr = Address::from_slice(&USDC_ADDRESS);
+            for (payee, share) in payees.into_iter().zip(shares) {
+                let disputed = self.disputed_balances.get(payee);
+                self.disputed_balances.setter(payee).set(checked_sub(disputed, share)?);
+                let balance = self.balances.getter(payee).get(usdc_addr);
+                self.balances.setter(payee).setter(usdc_addr).set(checked_add(balance, share)?);
+                self.vm().log(BalanceCredited {
+                    account: payee,
+                    token: usdc_addr,
+                    app_id: app_id_u256,
+                    amount: share,
+                });
+            }
+            // The share now sits in the ledger, so later refunds debit it there
+            self.purchase_disputed.setter(app_id_u256).setter(buyer).set(false);
+            self.purchase_escrow_release_days.setter(app_id_u256).setter(buyer).set(U256::ZERO);
+        }
+
+        self.vm().log(DisputeResolved {
+            app_id: app_id_u256,
+            buyer,
+            arbitrator: caller,
+            refunded: refund,
+            developer_share,
+        });
+
+        Ok(())
+    }
+
+    /// Move an account's unlocked escrow to its USDC ledger balance (anyone may call)
+    pub fn release_escrow(&mut self, account: Address) -> Result<(), Vec<u8>> {
+        self.settle_escrow(account)?;
+        Ok(())
+    }
+
+    // ============ License Token (ERC-1155) ============
+
+    /// Set an app's license terms (developer only)
+    ///
+    /// Licenses are soulbound unless `transferable` is set. `royalty_bps` is
+    /// the resale royalty, capped at 25%: royalty_info() reports it payable
+    /// to the developer, and pay_royalty() charges it through the ledger.
+    pub fn set_license_terms(&mut self, app_id: u64, transferable: bool, royalty_bps: u64) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let developer = self.__stylus_host.msg_sender();
+        if self.app_developers.get(app_id_u256) != developer {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if royalty_bps > MAX_ROYALTY_BPS {
+            return Err(ERROR_INVALID_ROYALTY.to_vec());
+        }
+
+        self.app_license_transferable.setter(app_id_u256).set(transferable);
+        self.app_royalty_bps.setter(app_id_u256).set(U256::from(royalty_bps));
+
+        self.vm().log(LicenseTermsSet {
+            app_id: app_id_u256,
+            developer,
+            transferable,
+            royalty_bps: U256::from(royalty_bps),
+        });
+
+        Ok(())
+    }
+
+    /// Pay the resale royalty on a license sale of `sale_price` USDC
+    ///
+    /// Marketplaces (or sellers) that settle through Varity call this after
+    /// approving this contract for the royalty. It is split like a purchase:
+    /// 90% to the app's payees and 10% to the treasury, credited to ledger
+    /// balances. Marketplaces that push ERC-2981 royalties pay the developer
+    /// directly instead (see royalty_info()).
+    pub fn pay_royalty(&mut self, app_id: u64, sale_price: U256) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let payer = self.__stylus_host.msg_sender();
+
+        let royalty_bps = to_u64(self.app_royalty_bps.get(app_id_u256))?;
+        let royalty = bps_of(sale_price, royalty_bps)?;
+        if royalty == U256::ZERO {
+            return Err(ERROR_INVALID_ROYALTY.to_vec());
+        }
+
+        // Pull first: nothing is credited unless the royalty arrived
+        let contract_addr = self.vm().contract_address();
+        self.usdc_transfer_from(payer, contract_addr, royalty)?;
+
+        let (developer_share, platform_fee) = self.credit_proceeds(app_id_u256, payer, royalty)?;
+
+        self.vm().log(RoyaltyPaid {
+            app_id: app_id_u256,
+            payer,
+            sale_price,
+            royalty,
+            developer_share,
+            platform_fee,
+        });
+
+        Ok(())
+    }
+
+    /// ERC-1155 balanceOf
+    pub fn balance_of(&self, account: Address, id: U256) -> Result<U256, Vec<u8>> {
+        Ok(self.license_balances.getter(id).get(account))
+    }
+
+    /// ERC-1155 balanceOfBatch
+    pub fn balance_of_batch(&self, accounts: Vec<Address>, ids: Vec<U256>) -> Result<Vec<U256>, Vec<u8>> {
+        if accounts.len() != ids.len() {
+            return Err(ERROR_INVALID_BATCH.to_vec());
+        }
+
+        Ok(accounts
+            .iter()
+            .zip(&ids)
+            .map(|(account, id)| self.license_balances.getter(*id).get(*account))
+            .collect())
+    }
+
+    /// ERC-1155 setApprovalForAll
+    pub fn set_approval_for_all(&mut self, operator: Address, approved: bool) -> Result<(), Vec<u8>> {
+        let account = self.__stylus_host.msg_sender();
+        self.operator_approvals.setter(account).setter(operator).set(approved);
+
+        self.vm().log(ApprovalForAll {
+            account,
+            operator,
+            approved,
+        });
+
+        Ok(())
+    }
+
+    /// ERC-1155 isApprovedForAll
+    pub fn is_approved_for_all(&self, account: Address, operator: Address) -> Result<bool, Vec<u8>> {
+        Ok(self.operator_approvals.getter(account).get(operator))
+    }
+
+    /// ERC-1155 safeTransferFrom (only for apps whose developer enabled transfers)
+    pub fn safe_transfer_from(
+        &mut self,
+        from: Address,
+        to: Address,
+        id: U256,
+        value: U256,
+        data: Bytes,
+    ) -> Result<(), Vec<u8>> {
+        let operator = self.__stylus_host.msg_sender();
+        self.move_licenses(operator, from, to, vec![id], vec![value])?;
+
+        self.vm().log(TransferSingle {
+            operator,
+            from,
+            to,
+            id,
+            value,
+        });
+
+        let calldata = onERC1155ReceivedCall {
+            operator,
+            from,
+            id,
+            value,
+            data,
+        }.abi_encode();
+        self.check_license_receiver(to, calldata, onERC1155ReceivedCall::SELECTOR.into())
+    }
+
+    /// ERC-1155 safeBatchTransferFrom (only for apps whose developer enabled transfers)
+    pub fn safe_batch_transfer_from(
+        &mut self,
+        from: Address,
+        to: Address,
+        ids: Vec<U256>,
+        values: Vec<U256>,
+        data: Bytes,
+    ) -> Result<(), Vec<u8>> {
+        let operator = self.__stylus_host.msg_sender();
+        self.move_licenses(operator, from, to, ids.clone(), values.clone())?;
+
+        self.vm().log(TransferBatch {
+            operator,
+            from,
+            to,
+            ids: ids.clone(),
+            values: values.clone(),
+        });
+
+        let calldata = onERC1155BatchReceivedCall {
+            operator,
+            from,
+            ids,
+            values,
+            data,
+        }.abi_encode();
+        self.check_license_receiver(to, calldata, onERC1155BatchReceivedCall::SELECTOR.into())
+    }
+
+    /// ERC-2981 royaltyInfo: marketplaces transfer the royalty straight to the
+    /// receiver, so it is the app developer (this contract only accounts for
+    /// royalties paid through pay_royalty())
+    pub fn royalty_info(&self, token_id: U256, sale_price: U256) -> Result<(Address, U256), Vec<u8>> {
+        let royalty_bps = to_u64(self.app_royalty_bps.get(token_id))?;
+        Ok((self.app_developers.get(token_id), bps_of(sale_price, royalty_bps)?))
+    }
+
+    /// ERC-165 supportsInterface
+    pub fn supports_interface(&self, interface_id: FixedBytes<4>) -> Result<bool, Vec<u8>> {
+        Ok([INTERFACE_ERC165, INTERFACE_ERC1155, INTERFACE_ERC2981].contains(&interface_id.0))
+    }
+
+    /// Get an app's license terms
+    pub fn get_license_terms(&self, app_id: u64) -> Result<(
+        bool,  // transferable
+        u64,   // royalty_bps
+    ), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+
+        Ok((
+            self.app_license_transferable.get(app_id_u256),
+            to_u64(self.app_royalty_bps.get(app_id_u256))?,
+        ))
+    }
+
+    // ============ Withdrawal Functions ============
+
+    /// Withdraw part of the caller's ledger balance for `token` to `to`
+    /// (unlocked escrow is released to the ledger first)
+    pub fn withdraw(&mut self, token: Address, amount: U256, to: Address) -> Result<(), Vec<u8>> {
+        let account = self.__stylus_host.msg_sender();
+        if token == Address::from_slice(&USDC_ADDRESS) {
+            self.settle_escrow(account)?;
+        }
+        self.withdraw_balance(account, token, amount, to)
+    }
+
+    /// Withdraw the caller's entire ledger balance for `token` to `to`
+    /// (unlocked escrow is released to the ledger first)
+    pub fn withdraw_all(&mut self, token: Address, to: Address) -> Result<(), Vec<u8>> {
+        let account = self.__stylus_host.msg_sender();
+        if token == Address::from_slice(&USDC_ADDRESS) {
+            self.settle_escrow(account)?;
+        }
+        let amount = self.balances.getter(account).get(token);
+        self.withdraw_balance(account, token, amount, to)
+    }
+
+    /// Sweep accumulated platform fees for `token` to the treasury (owner only)
+    pub fn sweep_treasury(&mut self, token: Address) -> Result<(), Vec<u8>> {
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        let treasury_addr = Address::from_slice(&TREASURY);
+        let amount = self.balances.getter(treasury_addr).get(token);
+        self.withdraw_balance(treasury_addr, token, amount, treasury_addr)
+    }
+
+    // ============ Billing Functions ============
+
+    /// Pay monthly bill for developer infrastructure — 100% to Varity treasury
+    ///
+    /// Uses ERC-20 USDC transferFrom. Developer must approve() this contract
+    /// for the bill amount before calling.
+    ///
+    /// # Arguments
+    /// * `app_id` - The app ID being billed for
+    /// * `period_hash` - Hash of billing period (e.g., keccak256("2026-02") truncated to u64)
+    /// * `amount` - Bill amount in USDC (6 decimals, e.g., 49_000_000 = $49)
+    pub fn pay_bill(&mut self, app_id: u64, period_hash: u64, amount: U256) -> Result<(), Vec<u8>> {
+        if app_id == 0 {
+            return Err(ERROR_INVALID_APP_ID.to_vec());
+        }
+        if period_hash == 0 {
+            return Err(ERROR_INVALID_PERIOD.to_vec());
+        }
+        if amount == U256::ZERO {
+            return Err(ERROR_INSUFFICIENT_PAYMENT.to_vec());
+        }
+
+        let developer = self.__stylus_host.msg_sender();
+        self.process_billing_payment(U256::from(app_id), U256::from(period_hash), developer, amount)
+    }
+
+    /// Pay an infrastructure bill using an EIP-2612 USDC permit instead of
+    /// approve() (see pay_bill and purchase_app_with_permit)
+    #[allow(clippy::too_many_arguments)]
+    pub fn pay_bill_with_permit(
+        &mut self,
+        app_id: u64,
+        period_hash: u64,
+        amount: U256,
+        deadline: U256,
+        v: u8,
+        r: B256,
+        s: B256,
+    ) -> Result<(), Vec<u8>> {
+        let developer = self.__stylus_host.msg_sender();
+        self.usdc_permit(developer, amount, deadline, v, r, s)?;
+        self.pay_bill(app_id, period_hash, amount)
+    }
+
+    /// Deposit prepaid infrastructure credit for the caller
+    ///
+    /// Uses ERC-20 USDC transferFrom. Developer must approve() this contract
+    /// for `amount` before calling. The billing role draws bills from the
+    /// credit with draw_credit() / pay_invoice_from_credit().
+    pub fn deposit_credit(&mut self, amount: U256) -> Result<(), Vec<u8>> {
+        if amount == U256::ZERO {
+            return Err(ERROR_INSUFFICIENT_PAYMENT.to_vec());
+        }
+
+        let developer = self.__stylus_host.msg_sender();
+        let contract_addr = self.vm().contract_address();
+
+        // === Effects BEFORE interactions ===
+        let previous = self.credit_balances.get(developer);
+        let balance = checked_add(previous, amount)?;
+        self.credit_balances.setter(developer).set(balance);
+
+        // === Interaction: ERC-20 USDC transfer (a failed pull reverts the credit) ===
+        self.usdc_transfer_from(developer, contract_addr, amount)?;
+
+        self.vm().log(CreditDeposited {
+            developer,
+            amount,
+            balance,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw unused prepaid credit to `to`
+    pub fn withdraw_credit(&mut self, amount: U256, to: Address) -> Result<(), Vec<u8>> {
+        if to == Address::ZERO {
+            return Err(ERROR_INVALID_RECIPIENT.to_vec());
+        }
+
+        let developer = self.__stylus_host.msg_sender();
+        let previous = self.credit_balances.get(developer);
+        if amount == U256::ZERO || amount > previous {
+            return Err(ERROR_INSUFFICIENT_BALANCE.to_vec());
+        }
+
+        // === Effects BEFORE interactions ===
+        let balance = checked_sub(previous, amount)?;
+        self.credit_balances.setter(developer).set(balance);
+
+        // === Interaction: ERC-20 USDC transfer (a failed payout reverts the debit) ===
+        let usdc_addr = Address::from_slice(&USDC_ADDRESS);
+        self.token_transfer(usdc_addr, to, amount)?;
+
+        self.vm().log(CreditWithdrawn {
+            developer,
+            to,
+            amount,
+            balance,
+        });
+        self.check_low_credit(developer, balance);
+
+        Ok(())
+    }
+
+    /// Set the caller's low-balance threshold for prepaid credit (0 disables alerts)
+    pub fn set_credit_threshold(&mut self, threshold: U256) -> Result<(), Vec<u8>> {
+        let developer = self.__stylus_host.msg_sender();
+        self.credit_low_thresholds.setter(developer).set(threshold);
+        Ok(())
+    }
+
+    /// Draw part of an invoice from the invoiced developer's prepaid credit
+    /// (admin or billing role only)
+    ///
+    /// The invoice must be issued to `developer` and the draw cannot exceed
+    /// its outstanding amount. Records the payment like pay_invoice(); the
+    /// USDC stays in this contract and is credited to the treasury's ledger
+    /// balance.
+    pub fn draw_credit(&mut self, developer: Address, invoice_id: u64, amount: U256) -> Result<(), Vec<u8>> {
+        let caller = self.__stylus_host.msg_sender();
+        if !self.billers.get(caller) && !self.is_admin_or_owner(caller) {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        let invoice_u256 = U256::from(invoice_id);
+        let app_id_u256 = self.invoice_app_ids.get(invoice_u256);
+        if app_id_u256 == U256::ZERO {
+            return Err(ERROR_INVOICE_NOT_FOUND.to_vec());
+        }
+        if self.invoice_developers.get(invoice_u256) != developer {
+            return Err(ERROR_INVOICE_DEVELOPER_MISMATCH.to_vec());
+        }
+        if amount == U256::ZERO {
+            return Err(ERROR_INSUFFICIENT_PAYMENT.to_vec());
+        }
+
+        let invoice_amount = self.invoice_amounts.get(invoice_u256);
+        let total_paid = checked_add(self.invoice_amounts_paid.get(invoice_u256), amount)?;
+        if total_paid > invoice_amount {
+            return Err(ERROR_INVOICE_OVERPAYMENT.to_vec());
+        }
+
+        let period_u256 = self.invoice_periods.get(invoice_u256);
+        self.process_credit_payment(app_id_u256, period_u256, developer, amount)?;
+        self.invoice_amounts_paid.setter(invoice_u256).set(total_paid);
+
+        self.vm().log(InvoicePaid {
+            invoice_id: invoice_u256,
+            app_id: app_id_u256,
+            payer: developer,
+            amount,
+            total_paid,
+            fully_paid: total_paid == invoice_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Pay the outstanding amount of an invoice from the invoiced developer's
+    /// prepaid credit (admin or billing role only)
+    pub fn pay_invoice_from_credit(&mut self, invoice_id: u64) -> Result<(), Vec<u8>> {
+        let caller = self.__stylus_host.msg_sender();
+        if !self.billers.get(caller) && !self.is_admin_or_owner(caller) {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        let invoice_u256 = U256::from(invoice_id);
+        let app_id_u256 = self.invoice_app_ids.get(invoice_u256);
+        if app_id_u256 == U256::ZERO {
+            return Err(ERROR_INVOICE_NOT_FOUND.to_vec());
+        }
+
+        let invoice_amount = self.invoice_amounts.get(invoice_u256);
+        let outstanding = checked_sub(invoice_amount, self.invoice_amounts_paid.get(invoice_u256))?;
+        if outstanding == U256::ZERO {
+            return Err(ERROR_INVOICE_OVERPAYMENT.to_vec());
+        }
+
+        let developer = self.invoice_developers.get(invoice_u256);
+        let period_u256 = self.invoice_periods.get(invoice_u256);
+        self.process_credit_payment(app_id_u256, period_u256, developer, outstanding)?;
+        self.invoice_amounts_paid.setter(invoice_u256).set(invoice_amount);
+
+        self.vm().log(InvoicePaid {
+            invoice_id: invoice_u256,
+            app_id: app_id_u256,
+            payer: developer,
+            amount: outstanding,
+            total_paid: invoice_amount,
+            fully_paid: true,
+        });
+
+        Ok(())
+    }
+
+    /// Issue an infrastructure invoice for an app (admin or billing role only)
+    ///
+    /// # Arguments
+    /// * `app_id` - The app ID being billed for
+    /// * `developer` - Developer responsible for the invoice
+    /// * `period_hash` - Hash of billing period (same scheme as pay_bill)
+    /// * `tier` - Infrastructure tier (1 = starter, 2 = growth, 3 = enterprise)
+    /// * `amount` - Invoice amount in USDC; 0 uses the period's amount due
+    ///   (tier monthly price plus metered overage, see get_period_amount_due)
+    /// * `due_date` - Unix timestamp after which the invoice is overdue
+    pub fn issue_invoice(
+        &mut self,
+        app_id: u64,
+        developer: Address,
+        period_hash: u64,
+        tier: u64,
+        amount: U256,
+        due_date: u64,
+    ) -> Result<u64, Vec<u8>> {
+        let caller = self.__stylus_host.msg_sender();
+        if !self.billers.get(caller) && !self.is_admin_or_owner(caller) {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if app_id == 0 {
+            return Err(ERROR_INVALID_APP_ID.to_vec());
+        }
+        if period_hash == 0 {
+            return Err(ERROR_INVALID_PERIOD.to_vec());
+        }
+        if developer == Address::ZERO {
+            return Err(ERROR_INVALID_RECIPIENT.to_vec());
+        }
+
+        let amount_due = self.period_amount_due(U256::from(app_id), U256::from(period_hash), tier)?;
+        let amount = if amount == U256::ZERO { amount_due } else { amount };
+
+        self.store_invoice(app_id, developer, period_hash, tier, amount, due_date)
+    }
+
+    /// Issue an infrastructure invoice prorated across the app's tier changes
+    /// (admin or billing role only)
+    ///
+    /// The amount is each tier's monthly price weighted by the share of
+    /// [`period_start`, `period_end`) the app spent on that tier, using the
+    /// tier history recorded with record_tier_change(). VarityAppRegistry is
+    /// the canonical record of tier changes: the billing role relays each of
+    /// its TierChanged events (including the tier at registration) before
+    /// invoicing. A period that starts before the app's first recorded tier
+    /// is rejected, since its history would be incomplete.
+    pub fn issue_prorated_invoice(
+        &mut self,
+        app_id: u64,
+        developer: Address,
+        period_hash: u64,
+        period_start: u64,
+        period_end: u64,
+        due_date: u64,
+    ) -> Result<u64, Vec<u8>> {
+        let caller = self.__stylus_host.msg_sender();
+        if !self.billers.get(caller) && !self.is_admin_or_owner(caller) {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if app_id == 0 {
+            return Err(ERROR_INVALID_APP_ID.to_vec());
+        }
+        if period_hash == 0 {
+            return Err(ERROR_INVALID_PERIOD.to_vec());
+        }
+        if developer == Address::ZERO {
+            return Err(ERROR_INVALID_RECIPIENT.to_vec());
+        }
+
+        let amount = self.prorated_amount(U256::from(app_id), period_start, period_end)?;
+        self.tier_at(U256::from(app_id), period_start)?;
+        let tier = self.tier_at(U256::from(app_id), period_end - 1)?;
+
+        self.store_invoice(app_id, developer, period_hash, tier, amount, due_date)
+    }
+
+    /// Record an app's tier change for billing (admin or billing role only)
+    ///
+    /// Mirrors VarityAppRegistry's TierChanged event onto this chain.
+    /// Changes must be recorded in order of `effective_at`.
+    pub fn record_tier_change(
+        &mut self,
+        app_id: u64,
+        new_tier: u64,
+        effective_at: u64,
+    ) -> Result<(), Vec<u8>> {
+        let caller = self.__stylus_host.msg_sender();
+        if !self.billers.get(caller) && !self.is_admin_or_owner(caller) {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if app_id == 0 {
+            return Err(ERROR_INVALID_APP_ID.to_vec());
+        }
+
+        let new_tier_u256 = U256::from(new_tier);
+        if new_tier_u256 >= self.tier_count.get() || !self.tier_is_active.get(new_tier_u256) {
+            return Err(ERROR_INVALID_TIER.to_vec());
+        }
+
+        let app_id_u256 = U256::from(app_id);
+        let count = self.app_tier_change_counts.get(app_id_u256);
+        let mut old_tier = U256::ZERO;
+        if count > U256::ZERO {
+            let last = count - U256::from(1);
+            old_tier = self.app_tier_change_tiers.getter(app_id_u256).get(last);
+            let last_time = self.app_tier_change_times.getter(app_id_u256).get(last);
+            if U256::from(effective_at) < last_time {
+                return Err(ERROR_INVALID_PERIOD.to_vec());
+            }
+        }
+
+        self.app_tier_change_tiers.setter(app_id_u256).setter(count).set(new_tier_u256);
+        self.app_tier_change_times.setter(app_id_u256).setter(count).set(U256::from(effective_at));
+        self.app_tier_change_counts.setter(app_id_u256).set(checked_add(count, U256::from(1))?);
+
+        self.vm().log(TierChanged {
+            app_id: app_id_u256,
+            old_tier,
+            new_tier: new_tier_u256,
+            effective_at: U256::from(effective_at),
+        });
+
+        Ok(())
+    }
+
+    /// Pay (fully or partially) an infrastructure invoice — 100% to Varity treasury
+    ///
+    /// Uses ERC-20 USDC transferFrom. Caller must approve() this contract
+    /// for the payment amount before calling. The payment is also recorded
+    /// against the invoice's billing period, as with pay_bill().
+    pub fn pay_invoice(&mut self, invoice_id: u64, amount: U256) -> Result<(), Vec<u8>> {
+        let invoice_u256 = U256::from(invoice_id);
+        let app_id_u256 = self.invoice_app_ids.get(invoice_u256);
+        if app_id_u256 == U256::ZERO {
+            return Err(ERROR_INVOICE_NOT_FOUND.to_vec());
+        }
+        if amount == U256::ZERO {
+            return Err(ERROR_INSUFFICIENT_PAYMENT.to_vec());
+        }
+
+        let invoice_amount = self.invoice_amounts.get(invoice_u256);
+        let already_paid = self.invoice_amounts_paid.get(invoice_u256);
+        let total_paid = checked_add(already_paid, amount)?;
+        if total_paid > invoice_amount {
+            return Err(ERROR_INVOICE_OVERPAYMENT.to_vec());
+        }
+
+        // === Effects BEFORE interactions ===
+        self.invoice_amounts_paid.setter(invoice_u256).set(total_paid);
+
+        let payer = self.__stylus_host.msg_sender();
+        let period_u256 = self.invoice_periods.get(invoice_u256);
+        self.process_billing_payment(app_id_u256, period_u256, payer, amount)?;
+
+        self.vm().log(InvoicePaid {
+            invoice_id: invoice_u256,
+            app_id: app_id_u256,
+            payer,
+            amount,
+            total_paid,
+            fully_paid: total_paid == invoice_amount,
+        });
+
+        Ok(())
+    }
+
+    // ============ Metering Functions ============
+
+    /// Report an app's cumulative transaction count for a period (reporter only)
+    ///
+    /// Counts are cumulative, so a report lower than the stored count is
+    /// rejected and a repeated report is a no-op.
+    pub fn report_usage(&mut self, app_id: u64, period_hash: u64, tx_count: u64) -> Result<(), Vec<u8>> {
+        let reporter = self.__stylus_host.msg_sender();
+        if !self.reporters.get(reporter) {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        self.store_usage(app_id, period_hash, tx_count, reporter)
+    }
+
+    /// Submit a batch of usage counts signed by a reporter (anyone may relay)
+    ///
+    /// The reporter signs get_usage_batch_digest() for the same arrays with
+    /// eth_sign / personal_sign. Replaying a batch is harmless because counts
+    /// are cumulative (see report_usage).
+    pub fn report_usage_batch(
+        &mut self,
+        app_ids: Vec<u64>,
+        period_hashes: Vec<u64>,
+        tx_counts: Vec<u64>,
+        v: u8,
+        r: B256,
+        s: B256,
+    ) -> Result<(), Vec<u8>> {
+        if app_ids.is_empty()
+            || app_ids.len() > MAX_USAGE_BATCH
+            || app_ids.len() != period_hashes.len()
+            || app_ids.len() != tx_counts.len()
+        {
+            return Err(ERROR_INVALID_BATCH.to_vec());
+        }
+
+        let digest = self.usage_batch_digest(app_ids.clone(), period_hashes.clone(), tx_counts.clone());
+        let reporter = self.recover_signer(digest, v, r, s)?;
+        if !self.reporters.get(reporter) {
+            return Err(ERROR_INVALID_SIGNATURE.to_vec());
+        }
+
+        for i in 0..app_ids.len() {
+            self.store_usage(app_ids[i], period_hashes[i], tx_counts[i], reporter)?;
+        }
+
+        Ok(())
+    }
+
+    // ============ View Functions ============
+
+    /// Get invoice details
+    #[allow(clippy::type_complexity)]
+    pub fn get_invoice(&self, invoice_id: u64) -> Result<(
+        u64,      // app_id
+        Address,  // developer
+        u64,      // period_hash
+        u64,      // tier
+        U256,     // amount
+        U256,     // amount_paid
+        u64,      // due_date
+        bool,     // is_paid
+        bool,     // is_overdue
+    ), Vec<u8>> {
+        let invoice_u256 = U256::from(invoice_id);
+        let app_id = self.invoice_app_ids.get(invoice_u256);
+        if app_id == U256::ZERO {
+            return Err(ERROR_INVOICE_NOT_FOUND.to_vec());
+        }
+
+        let amount = self.invoice_amounts.get(invoice_u256);
+        let amount_paid = self.invoice_amounts_paid.get(invoice_u256);
+        let due_date = to_u64(self.invoice_due_dates.get(invoice_u256))?;
+        let is_paid = amount_paid >= amount;
+        let is_overdue = !is_paid && self.__stylus_host.block_timestamp() > due_date;
+
+        Ok((
+            to_u64(app_id)?,
+            self.invoice_developers.get(invoice_u256),
+            to_u64(self.invoice_periods.get(invoice_u256))?,
+            to_u64(self.invoice_tiers.get(invoice_u256))?,
+            amount,
+            amount_paid,
+            due_date,
+            is_paid,
+            is_overdue,
+        ))
+    }
+
+    /// Get all invoice IDs issued for an app
+    pub fn get_app_invoices(&self, app_id: u64) -> Result<Vec<u64>, Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let count = to_u64(self.app_invoice_counts.get(app_id_u256))?;
+        let invoices = self.app_invoices.getter(app_id_u256);
+
+        let mut result = Vec::new();
+        for i in 0..count {
+            result.push(to_u64(invoices.get(U256::from(i)))?);
+        }
+
+        Ok(result)
+    }
+
+    /// Get an app's reported transaction count for a period
+    pub fn get_usage(&self, app_id: u64, period_hash: u64) -> Result<u64, Vec<u8>> {
+        to_u64(self.app_period_usage.getter(U256::from(app_id)).get(U256::from(period_hash)))
+    }
+
+    /// Get the overage charge for a period: transactions above the tier's
+    /// quota times the overage rate (0 for unmetered tiers)
+    pub fn get_overage_amount(&self, app_id: u64, period_hash: u64, tier: u64) -> Result<U256, Vec<u8>> {
+        self.overage_amount(U256::from(app_id), U256::from(period_hash), tier)
+    }
+
+    /// Get the amount due for a period: tier monthly price plus overage
+    ///
+    /// issue_invoice() uses this when called with amount 0; developers can
+    /// read it to size a pay_bill() payment.
+    pub fn get_period_amount_due(&self, app_id: u64, period_hash: u64, tier: u64) -> Result<U256, Vec<u8>> {
+        self.period_amount_due(U256::from(app_id), U256::from(period_hash), tier)
+    }
+
+    /// Get the overage price per transaction
+    pub fn get_overage_rate(&self) -> Result<U256, Vec<u8>> {
+        Ok(self.overage_rate_per_tx.get())
+    }
+
+    /// Get the digest a reporter signs for report_usage_batch()
+    pub fn get_usage_batch_digest(
+        &self,
+        app_ids: Vec<u64>,
+        period_hashes: Vec<u64>,
+        tx_counts: Vec<u64>,
+    ) -> Result<B256, Vec<u8>> {
+        Ok(self.usage_batch_digest(app_ids, period_hashes, tx_counts))
+    }
+
+    /// Check if an address has the usage reporter role
+    pub fn is_reporter(&self, account: Address) -> Result<bool, Vec<u8>> {
+        Ok(self.reporters.get(account))
+    }
+
+    /// Get an app's referral commission in basis points of the developer share
+    pub fn get_referral_share(&self, app_id: u64) -> Result<u64, Vec<u8>> {
+        to_u64(self.app_referral_shares.get(U256::from(app_id)))
+    }
+
+    /// Get an affiliate's registration status, lifetime commission earnings
+    /// and referred purchase count (both net of refunds)
+    pub fn get_affiliate(&self, affiliate: Address) -> Result<(bool, U256, U256), Vec<u8>> {
+        Ok((
+            self.affiliates.get(affiliate),
+            self.affiliate_earnings.get(affiliate),
+            self.affiliate_referral_counts.get(affiliate),
+        ))
+    }
+
+    /// Get an app's active revenue split (empty = all to the developer)
+    pub fn get_payees(&self, app_id: u64) -> Result<(Vec<Address>, Vec<u64>), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        self.split_entries(app_id_u256, self.app_split_versions.get(app_id_u256))
+    }
+
+    /// Get an app's revenue split awaiting owner confirmation
+    pub fn get_pending_payees(&self, app_id: u64) -> Result<(Vec<Address>, Vec<u64>), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        if !self.app_split_pending.get(app_id_u256) {
+            return Err(ERROR_NO_PENDING_SPLIT.to_vec());
+        }
+
+        let version = checked_add(self.app_split_versions.get(app_id_u256), U256::from(1))?;
+        self.split_entries(app_id_u256, version)
+    }
+
+    /// Get a payee's lifetime earnings from an app (net of refunds)
+    pub fn get_payee_earnings(&self, app_id: u64, payee: Address) -> Result<U256, Vec<u8>> {
+        Ok(self.app_payee_earnings.getter(U256::from(app_id)).get(payee))
+    }
+
+    /// Get a coupon
+    #[allow(clippy::type_complexity)]
+    pub fn get_coupon(&self, app_id: u64, code_hash: B256) -> Result<(
+        u64,   // percent_bps
+        U256,  // fixed_discount
+        u64,   // max_redemptions
+        u64,   // redemptions
+        u64,   // expires_at
+        bool,  // allowlist_only
+        bool,  // is_active
+    ), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+
+        Ok((
+            to_u64(self.coupon_percent_bps.getter(app_id_u256).get(code_hash))?,
+            self.coupon_fixed_discounts.getter(app_id_u256).get(code_hash),
+            to_u64(self.coupon_max_redemptions.getter(app_id_u256).get(code_hash))?,
+            to_u64(self.coupon_redemptions.getter(app_id_u256).get(code_hash))?,
+            to_u64(self.coupon_expires_at.getter(app_id_u256).get(code_hash))?,
+            self.coupon_allowlist_only.getter(app_id_u256).get(code_hash),
+            self.coupon_active.getter(app_id_u256).get(code_hash),
+        ))
+    }
+
+    /// Get the discount a coupon code gives `buyer` on an app's current price
+    /// (fails with the same error purchase_app_with_coupon would)
+    pub fn get_coupon_discount(&self, app_id: u64, code: String, buyer: Address) -> Result<U256, Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        self.coupon_discount(app_id_u256, keccak(code.as_bytes()), buyer, self.current_price(app_id_u256))
+    }
+
+    /// Get the next nonce for an account's signed requests
+    pub fn get_nonce(&self, account: Address) -> Result<U256, Vec<u8>> {
+        Ok(self.nonces.get(account))
+    }
+
+    /// Get the EIP-712 digest `buyer` signs for purchase_app_by_sig()
+    /// (using their current nonce)
+    pub fn get_purchase_app_digest(&self, app_id: u64, buyer: Address, max_price: U256, deadline: U256) -> Result<B256, Vec<u8>> {
+        Ok(self.purchase_app_digest(app_id, buyer, max_price, self.nonces.get(buyer), deadline))
+    }
+
+    /// Get a developer's prepaid infrastructure credit and low-balance threshold
+    pub fn get_credit(&self, developer: Address) -> Result<(U256, U256), Vec<u8>> {
+        Ok((
+            self.credit_balances.get(developer),
+            self.credit_low_thresholds.get(developer),
+        ))
+    }
+
+    /// Get the monthly infrastructure price for a tier
+    pub fn get_tier_price(&self, tier: u64) -> Result<U256, Vec<u8>> {
+        self.tier_price(tier)
+    }
+
+    /// Get a tier from the catalog
+    pub fn get_tier(&self, tier_id: u64) -> Result<(
+        String,  // name
+        U256,    // monthly_price
+        u64,     // tx_quota
+        bool,    // is_active
+    ), Vec<u8>> {
+        let tier_id_u256 = U256::from(tier_id);
+        if tier_id_u256 >= self.tier_count.get() {
+            return Err(ERROR_INVALID_TIER.to_vec());
+        }
+
+        Ok((
+            self.tier_names.get(tier_id_u256).get_string(),
+            self.tier_monthly_prices.get(tier_id_u256),
+            to_u64(self.tier_tx_quotas.get(tier_id_u256))?,
+            self.tier_is_active.get(tier_id_u256),
+        ))
+    }
+
+    /// Get number of tiers in the catalog
+    pub fn get_tier_count(&self) -> Result<u64, Vec<u8>> {
+        to_u64(self.tier_count.get())
+    }
+
+    /// Get an app's tier at a timestamp (from the recorded tier history)
+    pub fn get_app_tier_at(&self, app_id: u64, timestamp: u64) -> Result<u64, Vec<u8>> {
+        self.tier_at(U256::from(app_id), timestamp)
+    }
+
+    /// Get the infrastructure amount due for an app over a period, prorated
+    /// across its tier changes (see issue_prorated_invoice)
+    pub fn get_prorated_amount(
+        &self,
+        app_id: u64,
+        period_start: u64,
+        period_end: u64,
+    ) -> Result<U256, Vec<u8>> {
+        self.prorated_amount(U256::from(app_id), period_start, period_end)
+    }
+
+    /// Get an app's scheduled price change (new price, effective time; zeros if none)
+    pub fn get_pending_price(&self, app_id: u64) -> Result<(U256, u64), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let effective_at = self.app_pending_price_times.get(app_id_u256);
+        if effective_at <= U256::from(self.__stylus_host.block_timestamp()) {
+            return Ok((U256::ZERO, 0));
+        }
+
+        Ok((self.app_pending_prices.get(app_id_u256), to_u64(effective_at)?))
+    }
+
+    /// Get the number of entries in an app's price history
+    pub fn get_price_history_count(&self, app_id: u64) -> Result<u64, Vec<u8>> {
+        to_u64(self.price_history_counts.get(U256::from(app_id)))
+    }
+
+    /// Get a page of an app's price history (prices, effective times), oldest
+    /// first; includes a scheduled change not yet in effect
+    pub fn get_price_history(&self, app_id: u64, offset: u64, limit: u64) -> Result<(Vec<U256>, Vec<u64>), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let count = to_u64(self.price_history_counts.get(app_id_u256))?;
+        let end = offset.saturating_add(limit).min(count);
+
+        let entries = (offset.min(end)..end)
+            .map(|i| Ok((
+                self.price_history_prices.getter(app_id_u256).get(U256::from(i)),
+                to_u64(self.price_history_times.getter(app_id_u256).get(U256::from(i)))?,
+            )))
+            .collect::<Result<Vec<_>, Vec<u8>>>()?;
+        Ok(entries.into_iter().unzip())
+    }
+
+    /// Get app pricing details
+    pub fn get_app_pricing(&self, app_id: u64) -> Result<(
+        U256,     // price_usdc
+        Address,  // developer
+        bool,     // is_subscription
+        u64,      // interval_days
+        bool,     // is_active
+    ), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+
+        Ok((
+            self.current_price(app_id_u256),
+            self.app_developers.get(app_id_u256),
+            self.app_is_subscription.get(app_id_u256),
+            to_u64(self.app_interval_days.get(app_id_u256))?,
+            self.app_pricing_active.get(app_id_u256),
+        ))
+    }
+
+    /// Get an account's withdrawable ledger balance for a token
+    pub fn get_balance(&self, account: Address, token: Address) -> Result<U256, Vec<u8>> {
+        Ok(self.balances.getter(account).get(token))
+    }
+
+    /// Get the refund window for an app (in days, 0 = no refunds)
+    pub fn get_refund_window(&self, app_id: u64) -> Result<u64, Vec<u8>> {
+        to_u64(self.app_refund_window_days.get(U256::from(app_id)))
+    }
+
+    /// Get the pro-rata refund a subscriber of a deactivated app can claim
+    /// (0 if refunds are not offered or the paid period has ended)
+    pub fn get_subscription_refund(&self, app_id: u64, subscriber: Address) -> Result<U256, Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        if self.app_pricing_active.get(app_id_u256)
+            || !self.app_subscriber_refunds.get(app_id_u256)
+            || !self.has_purchased.getter(app_id_u256).get(subscriber)
+            || self.license_balances.getter(app_id_u256).get(subscriber) == U256::ZERO
+        {
+            return Ok(U256::ZERO);
+        }
+
+        let (unused, interval) = self.subscription_unused_time(app_id_u256, subscriber)?;
+        if unused == U256::ZERO {
+            return Ok(U256::ZERO);
+        }
+
+        let (platform_fee, referral_fee, _, shares) =
+            self.purchase_refund_parts(app_id_u256, subscriber, unused, interval)?;
+        let mut amount = checked_add(platform_fee, referral_fee)?;
+        for share in shares {
+            amount = checked_add(amount, share)?;
+        }
+        Ok(amount)
+    }
+
+    /// Get the escrow hold period for an app (in days, 0 = no escrow)
+    pub fn get_escrow_hold(&self, app_id: u64) -> Result<u64, Vec<u8>> {
+        to_u64(self.app_escrow_hold_days.get(U256::from(app_id)))
+    }
+
+    /// Get an account's escrowed USDC
+    pub fn get_escrow_balances(&self, account: Address) -> Result<(
+        U256,  // escrowed (still locked)
+        U256,  // releasable (unlocked, moved to the ledger on release or withdrawal)
+        U256,  // disputed
+    ), Vec<u8>> {
+        let releasable = self.releasable_escrow(account)?;
+
+        Ok((
+            checked_sub(self.escrow_balances.get(account), releasable)?,
+            releasable,
+            self.disputed_balances.get(account),
+        ))
+    }
+
+    /// Get a purchase's escrow state
+    pub fn get_purchase_escrow(&self, app_id: u64, buyer: Address) -> Result<(
+        u64,   // release_at (0 = not escrowed)
+        bool,  // disputed
+    ), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let release_day = self.purchase_escrow_release_days.getter(app_id_u256).get(buyer);
+
+        Ok((
+            to_u64(checked_mul(release_day, U256::from(SECONDS_PER_DAY))?)?,
+            self.purchase_disputed.getter(app_id_u256).get(buyer),
+        ))
+    }
+
+    /// Get a buyer's purchase record for an app
+    pub fn get_purchase_record(&self, app_id: u64, buyer: Address) -> Result<(
+        u64,   // purchased_at
+        U256,  // amount_paid
+        U256,  // platform_fee
+    ), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+
+        Ok((
+            to_u64(self.purchase_timestamps.getter(app_id_u256).get(buyer))?,
+            self.purchase_amounts.getter(app_id_u256).get(buyer),
+            self.purchase_platform_fees.getter(app_id_u256).get(buyer),
+        ))
+    }
+
+    /// Get the number of apps in a user's library (licenses held)
+    pub fn get_library_count(&self, user: Address) -> Result<u64, Vec<u8>> {
+        to_u64(self.library_counts.get(user))
+    }
+
+    /// Get a page of a user's library: app IDs, purchase (or claim) times
+    /// and, for subscriptions, the end of the paid interval (0 for lifetime
+    /// licenses). Licenses received by transfer have no purchase time but
+    /// keep the paid interval they carried.
+    #[allow(clippy::type_complexity)]
+    pub fn get_library(&self, user: Address, offset: u64, limit: u64) -> Result<(
+        Vec<u64>,  // app_ids
+        Vec<u64>,  // purchased_at
+        Vec<u64>,  // expires_at
+    ), Vec<u8>> {
+        let count = to_u64(self.library_counts.get(user))?;
+        let end = offset.saturating_add(limit).min(count);
+
+        let mut app_ids = Vec::new();
+        let mut purchased_at = Vec::new();
+        let mut expires_at = Vec::new();
+        for i in offset.min(end)..end {
+            let app_id = self.library_apps.getter(user).get(U256::from(i));
+            let purchased = self.purchase_timestamps.getter(app_id).get(user);
+            let expires = if self.app_is_subscription.get(app_id) {
+                self.subscription_ends_at(app_id, user)?
+            } else {
+                U256::ZERO
+            };
+            app_ids.push(to_u64(app_id)?);
+            purchased_at.push(to_u64(purchased)?);
+            expires_at.push(to_u64(expires)?);
+        }
+
+        Ok((app_ids, purchased_at, expires_at))
+    }
+
+    /// Check if a user holds a license for an app (purchased or received by transfer)
+    pub fn has_user_purchased(&self, app_id: u64, buyer: Address) -> Result<bool, Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        Ok(self.license_balances.getter(app_id_u256).get(buyer) > U256::ZERO)
+    }
+
+    /// Check if a user can access an app: holds a license or a seat, or is
+    /// within a free trial. For subscription apps, licenses and seats grant
+    /// access only until the end of their paid interval.
+    pub fn has_access(&self, app_id: u64, user: Address) -> Result<bool, Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let now = U256::from(self.__stylus_host.block_timestamp());
+        let is_subscription = self.app_is_subscription.get(app_id_u256);
+        let licensed = self.license_balances.getter(app_id_u256).get(user) > U256::ZERO
+            && (!is_subscription || now < self.subscription_ends_at(app_id_u256, user)?);
+        let org = self.seat_orgs.getter(app_id_u256).get(user);
+        let seated = org != Address::ZERO && self.seats_paid(app_id_u256, org);
+        Ok(licensed || seated || now < self.trial_ends_at.getter(app_id_u256).get(user))
+    }
+
+    /// Check if `member` holds a seat for an app that grants access (for
+    /// subscription apps, only until the end of the org's paid interval)
+    pub fn has_seat(&self, app_id: u64, member: Address) -> Result<bool, Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let org = self.seat_orgs.getter(app_id_u256).get(member);
+        Ok(org != Address::ZERO && self.seats_paid(app_id_u256, org))
+    }
+
+    /// Get the organization whose seat `member` holds (zero = none)
+    pub fn get_seat_org(&self, app_id: u64, member: Address) -> Result<Address, Vec<u8>> {
+        Ok(self.seat_orgs.getter(U256::from(app_id)).get(member))
+    }
+
+    /// Get an organization's seats for an app
+    pub fn get_seats(&self, app_id: u64, org: Address) -> Result<(
+        U256,  // total
+        U256,  // assigned
+        u64,   // paid_through (0 = lifetime seats of a one-time app)
+    ), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+
+        Ok((
+            self.org_seats.getter(app_id_u256).get(org),
+            self.org_seats_assigned.getter(app_id_u256).get(org),
+            to_u64(self.org_seats_paid_through.getter(app_id_u256).get(org))?,
+        ))
+    }
+
+    /// Get an app's seat volume discounts (min quantities, discounts in bps)
+    pub fn get_seat_discounts(&self, app_id: u64) -> Result<(Vec<u64>, Vec<u64>), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let count = to_u64(self.seat_discount_counts.get(app_id_u256))?;
+
+        let discounts = (0..count)
+            .map(|i| Ok((
+                to_u64(self.seat_discount_min_quantities.getter(app_id_u256).get(U256::from(i)))?,
+                to_u64(self.seat_discount_bps.getter(app_id_u256).get(U256::from(i)))?,
+            )))
+            .collect::<Result<Vec<_>, Vec<u8>>>()?;
+        Ok(discounts.into_iter().unzip())
+    }
+
+    /// Quote the price of `quantity` seats after volume discount
+    pub fn get_seat_price(&self, app_id: u64, quantity: u64) -> Result<U256, Vec<u8>> {
+        self.seat_price(U256::from(app_id), quantity)
+    }
+
+    /// Get an app's free trial length in days
+    pub fn get_trial_days(&self, app_id: u64) -> Result<u64, Vec<u8>> {
+        to_u64(self.app_trial_days.get(U256::from(app_id)))
+    }
+
+    /// Get a user's trial for an app
+    pub fn get_trial(&self, app_id: u64, user: Address) -> Result<(
+        u64,   // ends_at (0 = never started)
+        bool,  // converted
+    ), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+
+        Ok((
+            to_u64(self.trial_ends_at.getter(app_id_u256).get(user))?,
+            self.trial_converted.getter(app_id_u256).get(user),
+        ))
+    }
+
+    /// Get an app's trial funnel: trials started and trials converted
+    pub fn get_trial_stats(&self, app_id: u64) -> Result<(U256, U256), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        Ok((self.app_trial_starts.get(app_id_u256), self.app_trial_conversions.get(app_id_u256)))
+    }
+
+    /// Get billing payment for a period
+    pub fn get_billing_payment(&self, app_id: u64, period_hash: u64) -> Result<U256, Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        let period_u256 = U256::from(period_hash);
+        Ok(self.billing_payments.getter(app_id_u256).get(period_u256))
+    }
+
+    /// Get the platform fee (bps) a sale of an app is charged right now
+    pub fn get_fee_bps(&self, app_id: u64) -> Result<u64, Vec<u8>> {
+        self.fee_bps(U256::from(app_id))
+    }
+
+    /// Get a developer's fee override (active, fee_bps)
+    pub fn get_developer_fee(&self, developer: Address) -> Result<(bool, u64), Vec<u8>> {
+        Ok((
+            self.developer_fee_set.get(developer),
+            to_u64(self.developer_fee_bps.get(developer))?,
+        ))
+    }
+
+    /// Get an app's fee override (active, fee_bps)
+    pub fn get_app_fee(&self, app_id: u64) -> Result<(bool, u64), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+        Ok((
+            self.app_fee_set.get(app_id_u256),
+            to_u64(self.app_fee_bps.get(app_id_u256))?,
+        ))
+    }
+
+    /// Get the volume-based fee tiers (thresholds, fee_bps)
+    pub fn get_fee_tiers(&self) -> Result<(Vec<U256>, Vec<u64>), Vec<u8>> {
+        let count = to_u64(self.fee_tier_count.get())?;
+
+        let tiers = (0..count)
+            .map(|i| Ok((
+                self.fee_tier_thresholds.get(U256::from(i)),
+                to_u64(self.fee_tier_bps.get(U256::from(i)))?,
+            )))
+            .collect::<Result<Vec<_>, Vec<u8>>>()?;
+        Ok(tiers.into_iter().unzip())
+    }
+
+    /// Get total platform revenue (analytics)
+    pub fn get_total_platform_revenue(&self) -> Result<U256, Vec<u8>> {
+        Ok(self.total_platform_revenue.get())
+    }
+
+    /// Get total developer payouts (analytics)
+    pub fn get_total_developer_payouts(&self) -> Result<U256, Vec<u8>> {
+        Ok(self.total_developer_payouts.get())
+    }
+
+    /// Get gross revenue for an app (total paid by buyers, net of refunds)
+    pub fn get_app_gross_revenue(&self, app_id: u64) -> Result<U256, Vec<u8>> {
+        Ok(self.app_gross_revenue.get(U256::from(app_id)))
+    }
+
+    /// Get gross revenue for an app over a range of days (inclusive)
+    ///
+    /// Days are `block_timestamp / 86400`. The range is capped at
+    /// MAX_REVENUE_RANGE_DAYS to bound the cost of the call.
+    pub fn get_app_revenue_for_days(
+        &self,
+        app_id: u64,
+        from_day: u64,
+        to_day: u64,
+    ) -> Result<U256, Vec<u8>> {
+        if to_day < from_day || to_day - from_day >= MAX_REVENUE_RANGE_DAYS {
+            return Err(ERROR_INVALID_PERIOD.to_vec());
+        }
+
+        let app_id_u256 = U256::from(app_id);
+        let daily_revenue = self.app_daily_revenue.getter(app_id_u256);
+        let mut total = U256::ZERO;
+        for day in from_day..=to_day {
+            total = checked_add(total, daily_revenue.get(U256::from(day)))?;
+        }
+
+        Ok(total)
+    }
+
+    /// Get lifetime earnings for a developer (developer share, net of refunds)
+    pub fn get_developer_earnings(&self, developer: Address) -> Result<U256, Vec<u8>> {
+        Ok(self.developer_earnings.get(developer))
+    }
+
+    /// Get the number of purchases for an app (net of refunds)
+    pub fn get_app_purchase_count(&self, app_id: u64) -> Result<U256, Vec<u8>> {
+        Ok(self.app_purchase_counts.get(U256::from(app_id)))
+    }
+
+    /// Get the number of buyers who purchased or claimed an app over its
+    /// lifetime, net of refunds. This is not a live subscriber count: it
+    /// includes one-time buyers and is not reduced when a subscription lapses
+    /// or a license is transferred.
+    pub fn get_app_lifetime_buyers(&self, app_id: u64) -> Result<U256, Vec<u8>> {
+        Ok(self.app_lifetime_buyers.get(U256::from(app_id)))
+    }
+
+    /// Get the treasury address
+    pub fn get_treasury(&self) -> Result<Address, Vec<u8>> {
+        Ok(Address::from_slice(&TREASURY))
+    }
+
+    /// Get the USDC token address (Arbitrum One)
+    pub fn get_usdc_address(&self) -> Result<Address, Vec<u8>> {
+        Ok(Address::from_slice(&USDC_ADDRESS))
+    }
+
+    /// Get contract owner
+    pub fn get_owner(&self) -> Result<Address, Vec<u8>> {
+        Ok(self.owner.get())
+    }
+
+    /// Check if an address is an admin
+    pub fn is_admin(&self, account: Address) -> Result<bool, Vec<u8>> {
+        Ok(self.admins.get(account))
+    }
+
+    /// Check if an address has the billing role
+    pub fn is_biller(&self, account: Address) -> Result<bool, Vec<u8>> {
+        Ok(self.billers.get(account))
+    }
+
+    // ============ Admin Functions ============
+
+    /// Transfer ownership (owner only)
+    pub fn transfer_ownership(&mut self, new_owner: Address) -> Result<(), Vec<u8>> {
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if new_owner == Address::ZERO {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        self.owner.set(new_owner);
+        Ok(())
+    }
+
+    /// Grant or revoke admin rights (owner only)
+    pub fn set_admin(&mut self, account: Address, is_admin: bool) -> Result<(), Vec<u8>> {
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        self.admins.setter(account).set(is_admin);
+
+        self.vm().log(AdminUpdated {
+            admin: account,
+            is_admin,
+        });
+
+        Ok(())
+    }
+
+    /// Add or update a tier in the catalog (owner only)
+    ///
+    /// `tier_id` must be an existing ID or `tier_count` (to append a new tier).
+    /// An existing tier keeps its name, matching VarityAppRegistry's catalog.
+    /// Deactivating a tier prevents new invoices and tier changes to it.
+    /// Contracts initialized before the catalog existed are seeded through here.
+    pub fn set_tier(
+        &mut self,
+        tier_id: u64,
+        name: String,
+        monthly_price: U256,
+        tx_quota: u64,
+        is_active: bool,
+    ) -> Result<(), Vec<u8>> {
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        let tier_count = self.tier_count.get();
+        if U256::from(tier_id) > tier_count {
+            return Err(ERROR_INVALID_TIER.to_vec());
+        }
+        if name.is_empty() || name.len() > 32 {
+            return Err(ERROR_INVALID_INPUT.to_vec());
+        }
+
+        // Names are set once, on append, and stay unique
+        if U256::from(tier_id) < tier_count {
+            if self.tier_names.get(U256::from(tier_id)).get_string() != name {
+                return Err(ERROR_INVALID_TIER.to_vec());
+            }
+        } else if self.find_tier(name.clone()).is_ok() {
+            return Err(ERROR_INVALID_TIER.to_vec());
+        }
+
+        self.store_tier(tier_id, name.clone(), monthly_price, tx_quota, is_active);
+        if U256::from(tier_id) == tier_count {
+            self.tier_count.set(checked_add(tier_count, U256::from(1))?);
+        }
+
+        self.vm().log(TierUpdated {
+            tier_id: U256::from(tier_id),
+            name,
+            monthly_price,
+            tx_quota: U256::from(tx_quota),
+            is_active,
+        });
+
+        Ok(())
+    }
+
+    /// Confirm an app's proposed revenue split (owner only)
+    pub fn confirm_payees(&mut self, app_id: u64) -> Result<(), Vec<u8>> {
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        let app_id_u256 = U256::from(app_id);
+        if !self.app_split_pending.get(app_id_u256) {
+            return Err(ERROR_NO_PENDING_SPLIT.to_vec());
+        }
+
+        let version = checked_add(self.app_split_versions.get(app_id_u256), U256::from(1))?;
+        self.app_split_versions.setter(app_id_u256).set(version);
+        self.app_split_pending.setter(app_id_u256).set(false);
+
+        self.vm().log(PayeesConfirmed {
+            app_id: app_id_u256,
+            version,
+        });
+
+        Ok(())
+    }
+
+    /// Set or clear a developer's platform fee override (owner only)
+    pub fn set_developer_fee(&mut self, developer: Address, fee_bps: u64, active: bool) -> Result<(), Vec<u8>> {
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if fee_bps > PLATFORM_FEE_BPS {
+            return Err(ERROR_INVALID_FEE.to_vec());
+        }
+
+        self.developer_fee_set.setter(developer).set(active);
+        self.developer_fee_bps.setter(developer).set(U256::from(fee_bps));
+
+        self.vm().log(DeveloperFeeSet {
+            developer,
+            fee_bps: U256::from(fee_bps),
+            active,
+        });
+
+        Ok(())
+    }
+
+    /// Set or clear an app's platform fee override (owner only)
+    pub fn set_app_fee(&mut self, app_id: u64, fee_bps: u64, active: bool) -> Result<(), Vec<u8>> {
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if fee_bps > PLATFORM_FEE_BPS {
+            return Err(ERROR_INVALID_FEE.to_vec());
+        }
+
+        let app_id_u256 = U256::from(app_id);
+        self.app_fee_set.setter(app_id_u256).set(active);
+        self.app_fee_bps.setter(app_id_u256).set(U256::from(fee_bps));
+
+        self.vm().log(AppFeeSet {
+            app_id: app_id_u256,
+            fee_bps: U256::from(fee_bps),
+            active,
+        });
+
+        Ok(())
+    }
+
+    /// Set the escrow hold period for an app's purchases (admin only)
+    ///
+    /// Developer shares of later purchases unlock `hold_days` after purchase
+    /// (rounded up to a whole day); buyers can dispute until then. Seat
+    /// sales, renewals and royalties are held for the same time but cannot
+    /// be disputed. 0 turns escrow off.
+    pub fn set_escrow_hold(&mut self, app_id: u64, hold_days: u64) -> Result<(), Vec<u8>> {
+        if !self.is_admin_or_owner(self.__stylus_host.msg_sender()) {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if hold_days > MAX_ESCROW_HOLD_DAYS {
+            return Err(ERROR_INVALID_HOLD.to_vec());
+        }
+
+        let app_id_u256 = U256::from(app_id);
+        self.app_escrow_hold_days.setter(app_id_u256).set(U256::from(hold_days));
+
+        self.vm().log(EscrowHoldSet {
+            app_id: app_id_u256,
+            hold_days: U256::from(hold_days),
+        });
+
+        Ok(())
+    }
+
+    /// Replace the volume-based fee tiers (owner only)
+    ///
+    /// `thresholds` are lifetime developer earnings in USDC, strictly
+    /// ascending. Empty lists remove all tiers.
+    pub fn set_fee_tiers(&mut self, thresholds: Vec<U256>, fee_bps: Vec<u64>) -> Result<(), Vec<u8>> {
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if thresholds.len() != fee_bps.len() || thresholds.len() > MAX_FEE_TIERS {
+            return Err(ERROR_INVALID_FEE.to_vec());
+        }
+        if thresholds.windows(2).any(|pair| pair[0] >= pair[1])
+            || fee_bps.iter().any(|bps| *bps > PLATFORM_FEE_BPS)
+        {
+            return Err(ERROR_INVALID_FEE.to_vec());
+        }
+
+        for (i, (threshold, bps)) in thresholds.iter().zip(&fee_bps).enumerate() {
+            self.fee_tier_thresholds.setter(U256::from(i)).set(*threshold);
+            self.fee_tier_bps.setter(U256::from(i)).set(U256::from(*bps));
+        }
+        self.fee_tier_count.set(U256::from(thresholds.len()));
+
+        self.vm().log(FeeTiersSet { thresholds, fee_bps });
+
+        Ok(())
+    }
+
+    /// Grant or revoke the usage reporter role (owner only)
+    pub fn set_reporter(&mut self, account: Address, is_reporter: bool) -> Result<(), Vec<u8>> {
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        self.reporters.setter(account).set(is_reporter);
+
+        self.vm().log(ReporterUpdated {
+            reporter: account,
+            is_reporter,
+        });
+
+        Ok(())
+    }
+
+    /// Set the overage price per transaction above the tier quota (owner only)
+    pub fn set_overage_rate(&mut self, rate_per_tx: U256) -> Result<(), Vec<u8>> {
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        self.overage_rate_per_tx.set(rate_per_tx);
+
+        self.vm().log(OverageRateSet {
+            rate_per_tx,
+        });
+
+        Ok(())
+    }
+
+    /// Grant or revoke the billing role (owner only)
+    pub fn set_biller(&mut self, account: Address, is_biller: bool) -> Result<(), Vec<u8>> {
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+
+        self.billers.setter(account).set(is_biller);
+
+        self.vm().log(BillerUpdated {
+            biller: account,
+            is_biller,
+        });
+
+        Ok(())
+    }
+
+    // ============ Internal Helpers ============
+
+    /// Monthly infrastructure price for an active tier in the catalog
+    fn tier_price(&self, tier: u64) -> Result<U256, Vec<u8>> {
+        let tier_u256 = U256::from(tier);
+        if tier_u256 >= self.tier_count.get() || !self.tier_is_active.get(tier_u256) {
+            return Err(ERROR_INVALID_TIER.to_vec());
+        }
+
+        Ok(self.tier_monthly_prices.get(tier_u256))
+    }
+
+    /// Tier monthly price plus metered overage for a period
+    fn period_amount_due(&self, app_id: U256, period_hash: U256, tier: u64) -> Result<U256, Vec<u8>> {
+        checked_add(self.tier_price(tier)?, self.overage_amount(app_id, period_hash, tier)?)
+    }
+
+    /// Transactions above the tier quota times the overage rate
+    fn overage_amount(&self, app_id: U256, period_hash: U256, tier: u64) -> Result<U256, Vec<u8>> {
+        let tier_u256 = U256::from(tier);
+        if tier_u256 >= self.tier_count.get() {
+            return Err(ERROR_INVALID_TIER.to_vec());
+        }
+
+        // A quota of 0 means the tier is unmetered
+        let quota = self.tier_tx_quotas.get(tier_u256);
+        let usage = self.app_period_usage.getter(app_id).get(period_hash);
+        if quota == U256::ZERO || usage <= quota {
+            return Ok(U256::ZERO);
+        }
+
+        checked_mul(usage - quota, self.overage_rate_per_tx.get())
+    }
+
+    /// Store a cumulative usage count (must not decrease)
+    fn store_usage(&mut self, app_id: u64, period_hash: u64, tx_count: u64, reporter: Address) -> Result<(), Vec<u8>> {
+        if app_id == 0 {
+            return Err(ERROR_INVALID_APP_ID.to_vec());
+        }
+        if period_hash == 0 {
+            return Err(ERROR_INVALID_PERIOD.to_vec());
+        }
+
+        let app_id_u256 = U256::from(app_id);
+        let period_u256 = U256::from(period_hash);
+        let tx_count_u256 = U256::from(tx_count);
+        let current = self.app_period_usage.getter(app_id_u256).get(period_u256);
+        if tx_count_u256 < current {
+            return Err(ERROR_STALE_USAGE.to_vec());
+        }
+        if tx_count_u256 == current {
+            return Ok(());
+        }
+
+        self.app_period_usage.setter(app_id_u256).setter(period_u256).set(tx_count_u256);
+
+        self.vm().log(UsageReported {
+            app_id: app_id_u256,
+            period_hash: period_u256,
+            reporter,
+            tx_count: tx_count_u256,
+        });
+
+        Ok(())
+    }
+
+    /// eth_sign digest of a usage batch, bound to this contract and chain
+    fn usage_batch_digest(&self, app_ids: Vec<u64>, period_hashes: Vec<u64>, tx_counts: Vec<u64>) -> B256 {
+        let batch = UsageReportBatch {
+            verifying_contract: self.vm().contract_address(),
+            chain_id: U256::from(self.vm().chain_id()),
+            app_ids,
+            period_hashes,
+            tx_counts,
+        };
+
+        let mut message = b"\x19Ethereum Signed Message:\n32".to_vec();
+        message.extend_from_slice(keccak(batch.abi_encode()).as_slice());
+        keccak(message)
+    }
+
+    /// EIP-712 signing hash for a PurchaseApp request
+    fn purchase_app_digest(&self, app_id: u64, buyer: Address, max_price: U256, nonce: U256, deadline: U256) -> B256 {
+        let request = PurchaseApp {
+            app_id,
+            buyer,
+            max_price,
+            nonce,
+            deadline,
+        };
+        let domain = Eip712Domain::new(
+            Some(Cow::Borrowed("VarityPayments")),
+            Some(Cow::Borrowed("1")),
+            Some(U256::from(self.vm().chain_id())),
+            Some(self.vm().contract_address()),
+            None,
+        );
+        request.eip712_signing_hash(&domain)
+    }
+
+    /// Check a signed request from `signer` and consume their nonce
+    fn use_signature(
+        &mut self,
+        signer: Address,
+        digest: B256,
+        deadline: U256,
+        v: u8,
+        r: B256,
+        s: B256,
+    ) -> Result<(), Vec<u8>> {
+        if U256::from(self.__stylus_host.block_timestamp()) > deadline {
+            return Err(ERROR_SIGNATURE_EXPIRED.to_vec());
+        }
+        if signer == Address::ZERO || self.recover_signer(digest, v, r, s)? != signer {
+            return Err(ERROR_INVALID_SIGNATURE.to_vec());
+        }
+
+        let nonce = self.nonces.get(signer);
+        self.nonces.setter(signer).set(checked_add(nonce, U256::from(1))?);
+
+        Ok(())
+    }
+
+    /// Recover the signer of a digest with the ecrecover precompile
+    fn recover_signer(&self, digest: B256, v: u8, r: B256, s: B256) -> Result<Address, Vec<u8>> {
+        let mut input = Vec::with_capacity(128);
+        input.extend_from_slice(digest.as_slice());
+        input.extend_from_slice(&U256::from(v).to_be_bytes::<32>());
+        input.extend_from_slice(r.as_slice());
+        input.extend_from_slice(s.as_slice());
+
+        let output = unsafe {
+            RawCall::new_static(&self.__stylus_host)
+                .call(Address::from_slice(&ECRECOVER), &input)
+                .map_err(|_| ERROR_INVALID_SIGNATURE.to_vec())?
+        };
+
+        // Invalid signatures return no data
+        if output.len() != 32 {
+            return Err(ERROR_INVALID_SIGNATURE.to_vec());
+        }
+        let signer = Address::from_slice(&output[12..]);
+        if signer == Address::ZERO {
+            return Err(ERROR_INVALID_SIGNATURE.to_vec());
+        }
+
+        Ok(signer)
+    }
+
+    /// Write the default tiers as IDs 0..4, emitting TierUpdated for each
+    fn seed_tiers(&mut self) {
+        for (tier_id, (name, monthly_price, tx_quota)) in DEFAULT_TIERS.iter().enumerate() {
+            self.store_tier(tier_id as u64, name.to_string(), U256::from(*monthly_price), *tx_quota, true);
+            self.vm().log(TierUpdated {
+                tier_id: U256::from(tier_id),
+                name: name.to_string(),
+                monthly_price: U256::from(*monthly_price),
+                tx_quota: U256::from(*tx_quota),
+                is_active: true,
+            });
+        }
+        self.tier_count.set(U256::from(DEFAULT_TIERS.len()));
+    }
+
+    /// Look up a tier ID by name
+    fn find_tier(&self, name: String) -> Result<u64, Vec<u8>> {
+        let tier_count = to_u64(self.tier_count.get())?;
+        (0..tier_count)
+            .find(|id| self.tier_names.get(U256::from(*id)).get_string() == name)
+            .ok_or_else(|| ERROR_INVALID_TIER.to_vec())
+    }
+
+    /// Write a tier's fields to the catalog
+    fn store_tier(&mut self, tier_id: u64, name: String, monthly_price: U256, tx_quota: u64, is_active: bool) {
+        let tier_id_u256 = U256::from(tier_id);
+        self.tier_names.setter(tier_id_u256).set_str(&name);
+        self.tier_monthly_prices.setter(tier_id_u256).set(monthly_price);
+        self.tier_tx_quotas.setter(tier_id_u256).set(U256::from(tx_quota));
+        self.tier_is_active.setter(tier_id_u256).set(is_active);
+    }
+
+    /// An app's tier at `timestamp` from its recorded tier history
+    fn tier_at(&self, app_id: U256, timestamp: u64) -> Result<u64, Vec<u8>> {
+        let count = to_u64(self.app_tier_change_counts.get(app_id))?;
+        let tiers = self.app_tier_change_tiers.getter(app_id);
+        let times = self.app_tier_change_times.getter(app_id);
+
+        let index = (0..count)
+            .rev()
+            .map(U256::from)
+            .find(|i| times.get(*i) <= U256::from(timestamp))
+            .ok_or_else(|| ERROR_INVALID_TIER.to_vec())?;
+        to_u64(tiers.get(index))
+    }
+
+    /// Monthly tier prices weighted by time spent on each tier in [start, end)
+    fn prorated_amount(&self, app_id: U256, period_start: u64, period_end: u64) -> Result<U256, Vec<u8>> {
+        if period_end <= period_start {
+            return Err(ERROR_INVALID_PERIOD.to_vec());
+        }
+
+        let count = to_u64(self.app_tier_change_counts.get(app_id))?;
+        let tiers = self.app_tier_change_tiers.getter(app_id);
+        let times = self.app_tier_change_times.getter(app_id);
+
+        // Sum of price * seconds on that tier, divided by period length at the end
+        let mut weighted = U256::ZERO;
+        for i in 0..count {
+            let changed_at = to_u64(times.get(U256::from(i)))?;
+            let next_change = if i + 1 < count {
+                to_u64(times.get(U256::from(i + 1)))?
+            } else {
+                u64::MAX
+            };
+
+            let segment_start = changed_at.max(period_start);
+            let segment_end = next_change.min(period_end);
+            if segment_end > segment_start {
+                let price = self.tier_monthly_prices.get(tiers.get(U256::from(i)));
+                let seconds = U256::from(segment_end - segment_start);
+                weighted = checked_add(weighted, checked_mul(price, seconds)?)?;
+            }
+        }
+
+        Ok(weighted / U256::from(period_end - period_start))
+    }
+
+    /// Store a new invoice and add it to the app's invoice list
+    fn store_invoice(
+        &mut self,
+        app_id: u64,
+        developer: Address,
+        period_hash: u64,
+        tier: u64,
+        amount: U256,
+        due_date: u64,
+    ) -> Result<u64, Vec<u8>> {
+        if amount == U256::ZERO {
+            return Err(ERROR_INVALID_PRICE.to_vec());
+        }
+
+        // Assign invoice ID (IDs start at 1)
+        let mut invoice_id = self.next_invoice_id.get();
+        if invoice_id == U256::ZERO {
+            invoice_id = U256::from(1);
+        }
+        self.next_invoice_id.set(checked_add(invoice_id, U256::from(1))?);
+
+        let app_id_u256 = U256::from(app_id);
+        let now = U256::from(self.__stylus_host.block_timestamp());
+
+        self.invoice_app_ids.setter(invoice_id).set(app_id_u256);
+        self.invoice_developers.setter(invoice_id).set(developer);
+        self.invoice_periods.setter(invoice_id).set(U256::from(period_hash));
+        self.invoice_tiers.setter(invoice_id).set(U256::from(tier));
+        self.invoice_amounts.setter(invoice_id).set(amount);
+        self.invoice_due_dates.setter(invoice_id).set(U256::from(due_date));
+        self.invoice_issued_at.setter(invoice_id).set(now);
+
+        // Add to the app's invoice list
+        let count = self.app_invoice_counts.get(app_id_u256);
+        self.app_invoices.setter(app_id_u256).setter(count).set(invoice_id);
+        self.app_invoice_counts.setter(app_id_u256).set(checked_add(count, U256::from(1))?);
+
+        self.vm().log(InvoiceIssued {
+            invoice_id,
+            app_id: app_id_u256,
+            developer,
+            period_hash: U256::from(period_hash),
+            tier: U256::from(tier),
+            amount,
+            due_date: U256::from(due_date),
+        });
+
+        to_u64(invoice_id)
+    }
+
+    /// Record a billing payment for an app period and transfer it to the treasury
+    fn process_billing_payment(
+        &mut self,
+        app_id: U256,
+        period_hash: U256,
+        payer: Address,
+        amount: U256,
+    ) -> Result<(), Vec<u8>> {
+        let treasury_addr = Address::from_slice(&TREASURY);
+
+        // === Effects BEFORE interactions ===
+
+        // Record payment
+        let current_payment = self.billing_payments.getter(app_id).get(period_hash);
+        self.billing_payments
+            .setter(app_id)
+            .setter(period_hash)
+            .set(checked_add(current_payment, amount)?);
+
+        // Update analytics
+        let current_platform_rev = self.total_platform_revenue.get();
+        self.total_platform_revenue.set(checked_add(current_platform_rev, amount)?);
+
+        // === Interaction: ERC-20 USDC transfer ===
+
+        // Transfer USDC from payer to treasury (100%)
+        self.usdc_transfer_from(payer, treasury_addr, amount)?;
+
+        // Emit event
+        self.vm().log(BillingPayment {
+            app_id,
+            developer: payer,
+            amount,
+            period_hash,
+            timestamp: U256::from(self.__stylus_host.block_timestamp()),
+        });
+
+        Ok(())
+    }
+
+    /// Draw a billing payment from a developer's prepaid credit into the
+    /// treasury's ledger balance (no token transfer)
+    fn process_credit_payment(
+        &mut self,
+        app_id: U256,
+        period_hash: U256,
+        developer: Address,
+        amount: U256,
+    ) -> Result<(), Vec<u8>> {
+        let previous = self.credit_balances.get(developer);
+        if amount > previous {
+            return Err(ERROR_INSUFFICIENT_BALANCE.to_vec());
+        }
+        let balance = checked_sub(previous, amount)?;
+        self.credit_balances.setter(developer).set(balance);
+
+        // Move the USDC to the treasury's ledger balance
+        let usdc_addr = Address::from_slice(&USDC_ADDRESS);
+        let treasury_addr = Address::from_slice(&TREASURY);
+        let treasury_balance = self.balances.getter(treasury_addr).get(usdc_addr);
+        self.balances
+            .setter(treasury_addr)
+            .setter(usdc_addr)
+            .set(checked_add(treasury_balance, amount)?);
+
+        // Record payment
+        let current_payment = self.billing_payments.getter(app_id).get(period_hash);
+        self.billing_payments
+            .setter(app_id)
+            .setter(period_hash)
+            .set(checked_add(current_payment, amount)?);
+
+        // Update analytics
+        let current_platform_rev = self.total_platform_revenue.get();
+        self.total_platform_revenue.set(checked_add(current_platform_rev, amount)?);
+
+        // Emit events
+        self.vm().log(BalanceCredited {
+            account: treasury_addr,
+            token: usdc_addr,
+            app_id,
+            amount,
+        });
+        self.vm().log(CreditDrawn {
+            developer,
+            app_id,
+            period_hash,
+            amount,
+            balance,
+        });
+        self.vm().log(BillingPayment {
+            app_id,
+            developer,
+            amount,
+            period_hash,
+            timestamp: U256::from(self.__stylus_host.block_timestamp()),
+        });
+        self.check_low_credit(developer, balance);
+
+        Ok(())
+    }
+
+    /// Emit CreditLowBalance if a developer's credit is below their threshold
+    fn check_low_credit(&mut self, developer: Address, balance: U256) {
+        let threshold = self.credit_low_thresholds.get(developer);
+        if threshold > U256::ZERO && balance < threshold {
+            self.vm().log(CreditLowBalance {
+                developer,
+                balance,
+                threshold,
+            });
+        }
+    }
+
+    /// Charge `payer` for an app and grant access to `recipient`
+    ///
+    /// `coupon` is a coupon code hash, or zero for the list price.
+    fn process_purchase(
+        &mut self,
+        app_id: u64,
+        payer: Address,
+        recipient: Address,
+        coupon: B256,
+        referrer: Address,
+        max_price: U256,
+    ) -> Result<(), Vec<u8>> {
+        let app_id_u256 = U256::from(app_id);
+
+        // Check pricing is active
+        if !self.app_pricing_active.get(app_id_u256) {
+            return Err(ERROR_APP_NOT_FOR_SALE.to_vec());
+        }
+
+        let list_price = self.current_price(app_id_u256);
+        if list_price == U256::ZERO {
+            return Err(ERROR_APP_NOT_FOR_SALE.to_vec());
+        }
+
+        // Ownership follows the license: a holder cannot buy a second copy,
+        // while a buyer who transferred theirs can buy again (replacing the
+        // old purchase record, unless it is still under dispute)
+        if self.license_balances.getter(app_id_u256).get(recipient) > U256::ZERO {
+            return Err(ERROR_ALREADY_PURCHASED.to_vec());
+        }
+        if self.purchase_disputed.getter(app_id_u256).get(recipient) {
+            return Err(ERROR_ALREADY_DISPUTED.to_vec());
+        }
+
+        // Apply coupon
+        let mut discount = U256::ZERO;
+        if coupon != B256::ZERO {
+            discount = self.coupon_discount(app_id_u256, coupon, recipient, list_price)?;
+            let redemptions = self.coupon_redemptions.getter(app_id_u256).get(coupon);
+            self.coupon_redemptions
+                .setter(app_id_u256)
+                .setter(coupon)
+                .set(checked_add(redemptions, U256::from(1))?);
+        }
+        let price = checked_sub(list_price, discount)?;
+        if price > max_price {
+            return Err(ERROR_PRICE_ABOVE_MAX.to_vec());
+        }
+
+        // Calculate split (developer share and the app's platform fee)
+        let fee_bps = self.fee_bps(app_id_u256)?;
+        let platform_fee = bps_of(price, fee_bps)?;
+        let mut developer_share = checked_sub(price, platform_fee)?;
+
+        // Referral commission comes out of the developer share
+        let mut referral_fee = U256::ZERO;
+        let referral_share = to_u64(self.app_referral_shares.get(app_id_u256))?;
+        if referrer != Address::ZERO && referral_share > 0 {
+            referral_fee = bps_of(developer_share, referral_share)?;
+            developer_share = checked_sub(developer_share, referral_fee)?;
+        }
+        // Always overwrite, so no commission from an earlier purchase carries over
+        let paid_referrer = if referral_fee > U256::ZERO { referrer } else { Address::ZERO };
+        self.purchase_referrers.setter(app_id_u256).setter(recipient).set(paid_referrer);
+        self.purchase_referral_fees.setter(app_id_u256).setter(recipient).set(referral_fee);
+
+        let developer = self.app_developers.get(app_id_u256);
+        let treasury_addr = Address::from_slice(&TREASURY);
+
+        // === Effects BEFORE interactions (checks-effects-interactions pattern) ===
+
+        let now = U256::from(self.__stylus_host.block_timestamp());
+        let usdc_addr = Address::from_slice(&USDC_ADDRESS);
+        self.record_purchase(app_id_u256, payer, recipient, price, platform_fee, now)?;
+
+        // === Interaction: ERC-20 USDC transfer ===
+
+        // Pull the full price from payer into this contract (nothing to pull
+        // for a 100% coupon); a failed pull reverts the effects above
+        let contract_addr = self.vm().contract_address();
+        if price > U256::ZERO {
+            self.usdc_transfer_from(payer, contract_addr, price)?;
+        }
+
+        // Emit events
+        let version = self.app_split_versions.get(app_id_u256);
+        let release_day = self.purchase_escrow_release_days.getter(app_id_u256).get(recipient);
+        let (payees, shares) = self.split_developer_share(app_id_u256, version, developer_share)?;
+        for (payee, share) in payees.into_iter().zip(shares) {
+            if release_day == U256::ZERO {
+                self.vm().log(BalanceCredited {
+                    account: payee,
+                    token: usdc_addr,
+                    app_id: app_id_u256,
+                    amount: share,
+                });
+            } else {
+                self.vm().log(EscrowCredited {
+                    account: payee,
+                    app_id: app_id_u256,
+                    buyer: recipient,
+                    amount: share,
+                    release_at: release_day * U256::from(SECONDS_PER_DAY),
+                });
+            }
+        }
+        self.vm().log(BalanceCredited {
+            account: treasury_addr,
+            token: usdc_addr,
+            app_id: app_id_u256,
+            amount: platform_fee,
+        });
+        if referral_fee > U256::ZERO {
+            self.vm().log(BalanceCredited {
+                account: referrer,
+                token: usdc_addr,
+                app_id: app_id_u256,
+                amount: referral_fee,
+            });
+            self.vm().log(AffiliatePaid {
+                app_id: app_id_u256,
+                affiliate: referrer,
+                buyer: recipient,
+                amount: referral_fee,
+            });
+        }
+        if self.trial_ends_at.getter(app_id_u256).get(recipient) != U256::ZERO
+            && !self.trial_converted.getter(app_id_u256).get(recipient)
+        {
+            self.trial_converted.setter(app_id_u256).setter(recipient).set(true);
+            let conversions = self.app_trial_conversions.get(app_id_u256);
+            self.app_trial_conversions.setter(app_id_u256).set(checked_add(conversions, U256::from(1))?);
+            self.vm().log(TrialConverted {
+                app_id: app_id_u256,
+                user: recipient,
+                timestamp: now,
+            });
+        }
+        if coupon != B256::ZERO {
+            self.vm().log(CouponRedeemed {
+                app_id: app_id_u256,
+                code_hash: coupon,
+                buyer: recipient,
+                list_price,
+                discount,
+                redemptions: self.coupon_redemptions.getter(app_id_u256).get(coupon),
+            });
+        }
+        self.vm().log(TransferSingle {
+            operator: payer,
+            from: Address::ZERO,
+            to: recipient,
+            id: app_id_u256,
+            value: U256::from(1),
+        });
+        self.vm().log(AppPurchased {
+            app_id: app_id_u256,
+            recipient,
+            developer,
+            payer,
+            total_amount: price,
+            developer_share,
+            platform_fee,
+            fee_bps: U256::from(fee_bps),
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Payees and shares of a split version (empty = all to the developer)
+    fn split_entries(&self, app_id: U256, version: U256) -> Result<(Vec<Address>, Vec<u64>), Vec<u8>> {
+        let count = to_u64(self.split_payee_counts.getter(app_id).get(version))?;
+        let payees = self.split_payees.getter(app_id);
+        let shares = self.split_shares.getter(app_id);
+
+        let entries = (0..count)
+            .map(|i| Ok((
+                payees.getter(version).get(U256::from(i)),
+                to_u64(shares.getter(version).get(U256::from(i)))?,
+            )))
+            .collect::<Result<Vec<_>, Vec<u8>>>()?;
+        Ok(entries.into_iter().unzip())
+    }
+
+    /// Divide a developer share between a split version's payees; the last
+    /// payee receives the rounding remainder
+    fn split_developer_share(
+        &self,
+        app_id: U256,
+        version: U256,
+        developer_share: U256,
+    ) -> Result<(Vec<Address>, Vec<U256>), Vec<u8>> {
+        let (payees, shares_bps) = self.split_entries(app_id, version)?;
+        if payees.is_empty() {
+            return Ok((vec![self.app_developers.get(app_id)], vec![developer_share]));
+        }
+
+        let mut amounts = Vec::with_capacity(payees.len());
+        let mut remaining = developer_share;
+        for (i, bps) in shares_bps.iter().enumerate() {
+            let amount = if i + 1 == shares_bps.len() {
+                remaining
+            } else {
+                bps_of(developer_share, *bps)?
+            };
+            remaining = checked_sub(remaining, amount)?;
+            amounts.push(amount);
+        }
+
+        Ok((payees, amounts))
+    }
+
+    /// Discount a coupon gives `buyer` on `list_price`, after checking it is
+    /// active, unexpired, not exhausted and (if restricted) allowlisted
+    fn coupon_discount(&self, app_id: U256, code_hash: B256, buyer: Address, list_price: U256) -> Result<U256, Vec<u8>> {
+        if !self.coupon_active.getter(app_id).get(code_hash) {
+            return Err(ERROR_INVALID_COUPON.to_vec());
+        }
+
+        let expires_at = self.coupon_expires_at.getter(app_id).get(code_hash);
+        if expires_at != U256::ZERO && U256::from(self.__stylus_host.block_timestamp()) > expires_at {
+            return Err(ERROR_COUPON_EXPIRED.to_vec());
+        }
+
+        let max_redemptions = self.coupon_max_redemptions.getter(app_id).get(code_hash);
+        if max_redemptions != U256::ZERO && self.coupon_redemptions.getter(app_id).get(code_hash) >= max_redemptions {
+            return Err(ERROR_COUPON_EXHAUSTED.to_vec());
+        }
+
+        if self.coupon_allowlist_only.getter(app_id).get(code_hash)
+            && !self.coupon_allowlist.getter(app_id).getter(code_hash).get(buyer)
+        {
+            return Err(ERROR_COUPON_NOT_ALLOWED.to_vec());
+        }
+
+        let percent_bps = self.coupon_percent_bps.getter(app_id).get(code_hash);
+        if percent_bps > U256::ZERO {
+            return bps_of(list_price, to_u64(percent_bps)?);
+        }
+
+        // Fixed discounts are capped at the price
+        Ok(self.coupon_fixed_discounts.getter(app_id).get(code_hash).min(list_price))
+    }
+
+    /// Record a purchase: grant access, credit the 90/10 split to the
+    /// developer and treasury ledger balances, and update analytics
+    fn record_purchase(
+        &mut self,
+        app_id: U256,
+        payer: Address,
+        recipient: Address,
+        price: U256,
+        platform_fee: U256,
+        now: U256,
+    ) -> Result<(), Vec<u8>> {
+        // Any referral commission was recorded by the caller beforehand
+        let referrer = self.purchase_referrers.getter(app_id).get(recipient);
+        let referral_fee = self.purchase_referral_fees.getter(app_id).get(recipient);
+        let developer_share = checked_sub(checked_sub(price, platform_fee)?, referral_fee)?;
+        let developer = self.app_developers.get(app_id);
+        let usdc_addr = Address::from_slice(&USDC_ADDRESS);
+        let treasury_addr = Address::from_slice(&TREASURY);
+
+        // Mark as purchased
+        self.has_purchased.setter(app_id).setter(recipient).set(true);
+        self.purchase_payers.setter(app_id).setter(recipient).set(payer);
+        self.purchase_timestamps.setter(app_id).setter(recipient).set(now);
+        self.purchase_amounts.setter(app_id).setter(recipient).set(price);
+        self.purchase_platform_fees.setter(app_id).setter(recipient).set(platform_fee);
+        if self.app_is_subscription.get(app_id) {
+            let interval = checked_mul(self.app_interval_days.get(app_id), U256::from(SECONDS_PER_DAY))?;
+            self.set_paid_through(app_id, recipient, checked_add(now, interval)?);
+        }
+
+        // Mint the license token. Minting skips the ERC-1155 receiver hook so
+        // a purchase never calls into the recipient.
+        let licenses = self.license_balances.getter(app_id).get(recipient);
+        self.set_license_balance(app_id, recipient, checked_add(licenses, U256::from(1))?)?;
+
+        // Credit the split to the ledger (90% developer payees, 10% treasury).
+        // With an escrow hold, the payees' shares are escrowed instead.
+        let version = self.app_split_versions.get(app_id);
+        self.purchase_split_versions.setter(app_id).setter(recipient).set(version);
+        // (nothing to hold for a free claim or a 100% coupon)
+        let release_day = if price == U256::ZERO {
+            U256::ZERO
+        } else {
+            self.escrow_release_day(app_id, now)?
+        };
+        self.purchase_escrow_release_days.setter(app_id).setter(recipient).set(release_day);
+        let (payees, shares) = self.split_developer_share(app_id, version, developer_share)?;
+        for (payee, share) in payees.into_iter().zip(shares) {
+            if release_day == U256::ZERO {
+                let balance = self.balances.getter(payee).get(usdc_addr);
+                self.balances.setter(payee).setter(usdc_addr).set(checked_add(balance, share)?);
+            } else {
+                let held = self.escrow_balances.get(payee);
+                self.escrow_balances.setter(payee).set(checked_add(held, share)?);
+                let unlocking = self.escrow_unlocks.getter(payee).get(release_day);
+                self.escrow_unlocks.setter(payee).setter(release_day).set(checked_add(unlocking, share)?);
+            }
+            let earned = self.app_payee_earnings.getter(app_id).get(payee);
+            self.app_payee_earnings.setter(app_id).setter(payee).set(checked_add(earned, share)?);
+        }
+
+        let treasury_balance = self.balances.getter(treasury_addr).get(usdc_addr);
+        self.balances.setter(treasury_addr).setter(usdc_addr).set(checked_add(treasury_balance, platform_fee)?);
+
+        if referral_fee > U256::ZERO {
+            let referrer_balance = self.balances.getter(referrer).get(usdc_addr);
+            self.balances.setter(referrer).setter(usdc_addr).set(checked_add(referrer_balance, referral_fee)?);
+            let earned = self.affiliate_earnings.get(referrer);
+            self.affiliate_earnings.setter(referrer).set(checked_add(earned, referral_fee)?);
+            let referrals = self.affiliate_referral_counts.get(referrer);
+            self.affiliate_referral_counts.setter(referrer).set(checked_add(referrals, U256::from(1))?);
+        }
+
+        // Update analytics
+        let current_platform_rev = self.total_platform_revenue.get();
+        self.total_platform_revenue.set(checked_add(current_platform_rev, platform_fee)?);
+
+        let current_dev_payouts = self.total_developer_payouts.get();
+        self.total_developer_payouts.set(checked_add(current_dev_payouts, developer_share)?);
+
+        let day = now / U256::from(SECONDS_PER_DAY);
+        let gross = self.app_gross_revenue.get(app_id);
+        self.app_gross_revenue.setter(app_id).set(checked_add(gross, price)?);
+        let daily = self.app_daily_revenue.getter(app_id).get(day);
+        self.app_daily_revenue.setter(app_id).setter(day).set(checked_add(daily, price)?);
+        let earnings = self.developer_earnings.get(developer);
+        self.developer_earnings.setter(developer).set(checked_add(earnings, developer_share)?);
+        let purchases = self.app_purchase_counts.get(app_id);
+        self.app_purchase_counts.setter(app_id).set(checked_add(purchases, U256::from(1))?);
+        let buyers = self.app_lifetime_buyers.get(app_id);
+        self.app_lifetime_buyers.setter(app_id).set(checked_add(buyers, U256::from(1))?);
+
+        Ok(())
+    }
+
+    /// Undo `part / whole` of `record_purchase` (1 / 1 for a full refund):
+    /// revoke access, debit that part of the split from the payees, treasury
+    /// and referrer, and reverse analytics by the same figures.
+    /// Returns the payer of the cleared purchase and the amount debited.
+    fn clear_purchase(
+        &mut self,
+        app_id: U256,
+        recipient: Address,
+        part: U256,
+        whole: U256,
+    ) -> Result<(Address, U256), Vec<u8>> {
+        let payer = self.purchase_payers.getter(app_id).get(recipient);
+        let purchased_at = self.purchase_timestamps.getter(app_id).get(recipient);
+        let referrer = self.purchase_referrers.getter(app_id).get(recipient);
+        let (platform_fee, referral_fee, payees, shares) = self.purchase_refund_parts(app_id, recipient, part, whole)?;
+        let developer = self.app_developers.get(app_id);
+        let usdc_addr = Address::from_slice(&USDC_ADDRESS);
+        let treasury_addr = Address::from_slice(&TREASURY);
+
+        // Revoke access and clear the purchase record
+        self.has_purchased.setter(app_id).setter(recipient).set(false);
+        self.purchase_payers.setter(app_id).setter(recipient).set(Address::ZERO);
+        self.purchase_timestamps.setter(app_id).setter(recipient).set(U256::ZERO);
+        self.purchase_amounts.setter(app_id).setter(recipient).set(U256::ZERO);
+        self.purchase_platform_fees.setter(app_id).setter(recipient).set(U256::ZERO);
+        self.purchase_referrers.setter(app_id).setter(recipient).set(Address::ZERO);
+        self.purchase_referral_fees.setter(app_id).setter(recipient).set(U256::ZERO);
+        self.subscription_paid_through.setter(app_id).setter(recipient).set(U256::ZERO);
+
+        // Burn the license token
+        let licenses = self.license_balances.getter(app_id).get(recipient);
+        self.set_license_balance(app_id, recipient, checked_sub(licenses, U256::from(1))?)?;
+
+        // Debit the split from the ledger (using the split the purchase was
+        // credited under), or from escrow while the share is still held there
+        let held = self.escrow_held(app_id, recipient);
+        let disputed = self.purchase_disputed.getter(app_id).get(recipient);
+        let release_day = self.purchase_escrow_release_days.getter(app_id).get(recipient);
+        self.purchase_split_versions.setter(app_id).setter(recipient).set(U256::ZERO);
+        self.purchase_disputed.setter(app_id).setter(recipient).set(false);
+        self.purchase_escrow_release_days.setter(app_id).setter(recipient).set(U256::ZERO);
+        let mut developer_share = U256::ZERO;
+        for (payee, share) in payees.into_iter().zip(shares) {
+            if disputed {
+                let balance = self.disputed_balances.get(payee);
+                self.disputed_balances.setter(payee).set(checked_sub(balance, share)?);
+            } else if held {
+                let balance = self.escrow_balances.get(payee);
+                self.escrow_balances.setter(payee).set(checked_sub(balance, share)?);
+                let unlocking = self.escrow_unlocks.getter(payee).get(release_day);
+                self.escrow_unlocks.setter(payee).setter(release_day).set(checked_sub(unlocking, share)?);
+            } else {
+                let balance = self.balances.getter(payee).get(usdc_addr);
+                self.balances.setter(payee).setter(usdc_addr).set(checked_sub(balance, share)?);
+            }
+            let earned = self.app_payee_earnings.getter(app_id).get(payee);
+            self.app_payee_earnings.setter(app_id).setter(payee).set(checked_sub(earned, share)?);
+            developer_share = checked_add(developer_share, share)?;
+        }
+        let amount = checked_add(checked_add(developer_share, platform_fee)?, referral_fee)?;
+
+        let treasury_balance = self.balances.getter(treasury_addr).get(usdc_addr);
+        self.balances.setter(treasury_addr).setter(usdc_addr).set(checked_sub(treasury_balance, platform_fee)?);
+
+        if referral_fee > U256::ZERO {
+            let referrer_balance = self.balances.getter(referrer).get(usdc_addr);
+            self.balances.setter(referrer).setter(usdc_addr).set(checked_sub(referrer_balance, referral_fee)?);
+            let earned = self.affiliate_earnings.get(referrer);
+            self.affiliate_earnings.setter(referrer).set(checked_sub(earned, referral_fee)?);
+            let referrals = self.affiliate_referral_counts.get(referrer);
+            self.affiliate_referral_counts.setter(referrer).set(checked_sub(referrals, U256::from(1))?);
+        }
+
+        // Reverse analytics
+        let current_platform_rev = self.total_platform_revenue.get();
+        self.total_platform_revenue.set(checked_sub(current_platform_rev, platform_fee)?);
+
+        let current_dev_payouts = self.total_developer_payouts.get();
+        self.total_developer_payouts.set(checked_sub(current_dev_payouts, developer_share)?);
+
+        let day = purchased_at / U256::from(SECONDS_PER_DAY);
+        let gross = self.app_gross_revenue.get(app_id);
+        self.app_gross_revenue.setter(app_id).set(checked_sub(gross, amount)?);
+        let daily = self.app_daily_revenue.getter(app_id).get(day);
+        self.app_daily_revenue.setter(app_id).setter(day).set(checked_sub(daily, amount)?);
+        let earnings = self.developer_earnings.get(developer);
+        self.developer_earnings.setter(developer).set(checked_sub(earnings, developer_share)?);
+        let purchases = self.app_purchase_counts.get(app_id);
+        self.app_purchase_counts.setter(app_id).set(checked_sub(purchases, U256::from(1))?);
+        let buyers = self.app_lifetime_buyers.get(app_id);
+        self.app_lifetime_buyers.setter(app_id).set(checked_sub(buyers, U256::from(1))?);
+
+        Ok((payer, amount))
+    }
+
+    /// Check `part / whole` of a purchase can be clawed back from ledger
+    /// balances (fails if already withdrawn or swept); a share still held in
+    /// escrow or under dispute is debited there
+    fn check_clawback(&mut self, app_id: U256, buyer: Address, part: U256, whole: U256) -> Result<(), Vec<u8>> {
+        let referrer = self.purchase_referrers.getter(app_id).get(buyer);
+        let (platform_fee, referral_fee, payees, shares) = self.purchase_refund_parts(app_id, buyer, part, whole)?;
+        let usdc_addr = Address::from_slice(&USDC_ADDRESS);
+        let treasury_addr = Address::from_slice(&TREASURY);
+
+        if self.balances.getter(referrer).get(usdc_addr) < referral_fee {
+            return Err(ERROR_INSUFFICIENT_BALANCE.to_vec());
+        }
+        if !self.escrow_held(app_id, buyer) {
+            for (payee, share) in payees.into_iter().zip(shares) {
+                self.settle_escrow(payee)?;
+                if self.balances.getter(payee).get(usdc_addr) < share {
+                    return Err(ERROR_INSUFFICIENT_BALANCE.to_vec());
+                }
+            }
+        }
+        if self.balances.getter(treasury_addr).get(usdc_addr) < platform_fee {
+            return Err(ERROR_INSUFFICIENT_BALANCE.to_vec());
+        }
+
+        Ok(())
+    }
+
+    /// Refund a purchase to its payer: check the split can be clawed back,
+    /// clear the purchase, credit the payer and emit the refund events
+    fn refund(&mut self, app_id: U256, buyer: Address, caller: Address) -> Result<(), Vec<u8>> {
+        let now = U256::from(self.__stylus_host.block_timestamp());
+        let platform_fee = self.purchase_platform_fees.getter(app_id).get(buyer);
+        let (developer_share, _, _) = self.purchase_developer_shares(app_id, buyer)?;
+        let usdc_addr = Address::from_slice(&USDC_ADDRESS);
+
+        // The license is burned, so the buyer must still hold it
+        if self.license_balances.getter(app_id).get(buyer) == U256::ZERO {
+            return Err(ERROR_LICENSE_TRANSFERRED.to_vec());
+        }
+
+        // Revoke access, debit the split and reverse analytics
+        self.check_clawback(app_id, buyer, U256::from(1), U256::from(1))?;
+        let (payer, amount) = self.clear_purchase(app_id, buyer, U256::from(1), U256::from(1))?;
+
+        // Credit the full amount to the payer
+        let payer_balance = self.balances.getter(payer).get(usdc_addr);
+        self.balances.setter(payer).setter(usdc_addr).set(checked_add(payer_balance, amount)?);
+
+        // Emit events
+        self.vm().log(BalanceCredited {
+            account: payer,
+            token: usdc_addr,
+            app_id,
+            amount,
+        });
+        self.vm().log(TransferSingle {
+            operator: caller,
+            from: buyer,
+            to: Address::ZERO,
+            id: app_id,
+            value: U256::from(1),
+        });
+        self.vm().log(PurchaseRefunded {
+            app_id,
+            buyer,
+            refunded_by: caller,
+            total_amount: amount,
+            developer_share,
+            platform_fee,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Check if an address is the owner or an admin
+    fn is_admin_or_owner(&self, account: Address) -> bool {
+        account == self.owner.get() || self.admins.get(account)
+    }
+
+    /// Day an escrow placed at `now` unlocks: the app's hold period, rounded
+    /// up to a whole day (0 when the app has no hold period)
+    fn escrow_release_day(&self, app_id: U256, now: U256) -> Result<U256, Vec<u8>> {
+        let hold_days = self.app_escrow_hold_days.get(app_id);
+        if hold_days == U256::ZERO {
+            return Ok(U256::ZERO);
+        }
+
+        let day = U256::from(SECONDS_PER_DAY);
+        let unlocks_at = checked_add(now, checked_mul(hold_days, day)?)?;
+        Ok(unlocks_at.div_ceil(day))
+    }
+
+    /// Whether an org's seats are paid for now (always, for one-time apps)
+    fn seats_paid(&self, app_id: U256, org: Address) -> bool {
+        let now = U256::from(self.__stylus_host.block_timestamp());
+        !self.app_is_subscription.get(app_id) || now < self.org_seats_paid_through.getter(app_id).get(org)
+    }
+
+    /// Set a subscriber's paid-through time, tracking the app's latest
+    fn set_paid_through(&mut self, app_id: U256, subscriber: Address, paid_through: U256) {
+        self.subscription_paid_through.setter(app_id).setter(subscriber).set(paid_through);
+        if paid_through > self.app_latest_paid_through.get(app_id) {
+            self.app_latest_paid_through.setter(app_id).set(paid_through);
+        }
+    }
+
+    /// End of a subscriber's paid interval. Subscriptions bought before
+    /// paid-through tracking end one interval after purchase.
+    fn subscription_ends_at(&self, app_id: U256, subscriber: Address) -> Result<U256, Vec<u8>> {
+        let paid_through = self.subscription_paid_through.getter(app_id).get(subscriber);
+        if paid_through != U256::ZERO {
+            return Ok(paid_through);
+        }
+
+        let interval = checked_mul(self.app_interval_days.get(app_id), U256::from(SECONDS_PER_DAY))?;
+        checked_add(self.purchase_timestamps.getter(app_id).get(subscriber), interval)
+    }
+
+    /// Unused time of a subscriber's paid interval and the interval length;
+    /// a renewed subscription is refunded at most one interval
+    fn subscription_unused_time(&self, app_id: U256, subscriber: Address) -> Result<(U256, U256), Vec<u8>> {
+        let now = U256::from(self.__stylus_host.block_timestamp());
+        let ends_at = self.subscription_ends_at(app_id, subscriber)?;
+        let interval = checked_mul(self.app_interval_days.get(app_id), U256::from(SECONDS_PER_DAY))?;
+        if now >= ends_at || interval == U256::ZERO {
+            return Ok((U256::ZERO, interval));
+        }
+
+        Ok(((ends_at - now).min(interval), interval))
+    }
+
+    /// `part / whole` of a purchase's platform fee, referral commission and
+    /// each payee's developer share (all of it for 1 / 1)
+    #[allow(clippy::type_complexity)]
+    fn purchase_refund_parts(
+        &self,
+        app_id: U256,
+        buyer: Address,
+        part: U256,
+        whole: U256,
+    ) -> Result<(U256, U256, Vec<Address>, Vec<U256>), Vec<u8>> {
+        let scale = |amount: U256| -> Result<U256, Vec<u8>> { Ok(checked_mul(amount, part)? / whole) };
+        let platform_fee = scale(self.purchase_platform_fees.getter(app_id).get(buyer))?;
+        let referral_fee = scale(self.purchase_referral_fees.getter(app_id).get(buyer))?;
+        let (_, payees, shares) = self.purchase_developer_shares(app_id, buyer)?;
+        let shares = shares.into_iter().map(scale).collect::<Result<Vec<U256>, Vec<u8>>>()?;
+        Ok((platform_fee, referral_fee, payees, shares))
+    }
+
+    /// Whether a purchase's developer share is still in escrow (locked or disputed)
+    fn escrow_held(&self, app_id: U256, buyer: Address) -> bool {
+        let today = U256::from(self.__stylus_host.block_timestamp()) / U256::from(SECONDS_PER_DAY);
+        self.purchase_disputed.getter(app_id).get(buyer)
+            || self.purchase_escrow_release_days.getter(app_id).get(buyer) > today
+    }
+
+    /// A purchase's developer share and its division between the payees of
+    /// the split it was credited under
+    fn purchase_developer_shares(
+        &self,
+        app_id: U256,
+        buyer: Address,
+    ) -> Result<(U256, Vec<Address>, Vec<U256>), Vec<u8>> {
+        let price = self.purchase_amounts.getter(app_id).get(buyer);
+        let platform_fee = self.purchase_platform_fees.getter(app_id).get(buyer);
+        let referral_fee = self.purchase_referral_fees.getter(app_id).get(buyer);
+        let developer_share = checked_sub(checked_sub(price, platform_fee)?, referral_fee)?;
+        let version = self.purchase_split_versions.getter(app_id).get(buyer);
+        let (payees, shares) = self.split_developer_share(app_id, version, developer_share)?;
+        Ok((developer_share, payees, shares))
+    }
+
+    /// Escrow held for `account` that has unlocked: everything not due to
+    /// unlock on a later day
+    fn releasable_escrow(&self, account: Address) -> Result<U256, Vec<u8>> {
+        let held = self.escrow_balances.get(account);
+        if held == U256::ZERO {
+            return Ok(U256::ZERO);
+        }
+
+        let today = self.__stylus_host.block_timestamp() / SECONDS_PER_DAY;
+        let unlocks = self.escrow_unlocks.getter(account);
+        let mut locked = U256::ZERO;
+        for day in (today + 1)..=(today + MAX_ESCROW_HOLD_DAYS + 1) {
+            locked = checked_add(locked, unlocks.get(U256::from(day)))?;
+        }
+
+        checked_sub(held, locked)
+    }
+
+    /// Move an account's unlocked escrow to its USDC ledger balance
+    fn settle_escrow(&mut self, account: Address) -> Result<U256, Vec<u8>> {
+        let amount = self.releasable_escrow(account)?;
+        if amount == U256::ZERO {
+            return Ok(amount);
+        }
+
+        let usdc_addr = Address::from_slice(&USDC_ADDRESS);
+        let held = self.escrow_balances.get(account);
+        self.escrow_balances.setter(account).set(checked_sub(held, amount)?);
+        let balance = self.balances.getter(account).get(usdc_addr);
+        self.balances.setter(account).setter(usdc_addr).set(checked_add(balance, amount)?);
+
+        self.vm().log(EscrowReleased { account, amount });
+
+        Ok(amount)
+    }
+
+    /// Credit `amount` of app proceeds paid by `payer` to the app's payees
+    /// and the treasury at the app's platform fee, returning
+    /// (developer_share, platform_fee)
+    ///
+    /// With an escrow hold, the payees' shares are escrowed and unlock on
+    /// the same schedule as a purchase's. They are not tied to a purchase
+    /// record, so they cannot be disputed.
+    fn credit_proceeds(&mut self, app_id: U256, payer: Address, amount: U256) -> Result<(U256, U256), Vec<u8>> {
+        let platform_fee = bps_of(amount, self.fee_bps(app_id)?)?;
+        let developer_share = checked_sub(amount, platform_fee)?;
+        let usdc_addr = Address::from_slice(&USDC_ADDRESS);
+        let treasury_addr = Address::from_slice(&TREASURY);
+
+        let now = U256::from(self.__stylus_host.block_timestamp());
+        let release_day = self.escrow_release_day(app_id, now)?;
+        let version = self.app_split_versions.get(app_id);
+        let (payees, shares) = self.split_developer_share(app_id, version, developer_share)?;
+        for (payee, share) in payees.into_iter().zip(shares) {
+            if release_day == U256::ZERO {
+                let balance = self.balances.getter(payee).get(usdc_addr);
+                self.balances.setter(payee).setter(usdc_addr).set(checked_add(balance, share)?);
+                self.vm().log(BalanceCredited {
+                    account: payee,
+                    token: usdc_addr,
+                    app_id,
+                    amount: share,
+                });
+            } else {
+                let held = self.escrow_balances.get(payee);
+                self.escrow_balances.setter(payee).set(checked_add(held, share)?);
+                let unlocking = self.escrow_unlocks.getter(payee).get(release_day);
+                self.escrow_unlocks.setter(payee).setter(release_day).set(checked_add(unlocking, share)?);
+                self.vm().log(EscrowCredited {
+                    account: payee,
+                    app_id,
+                    buyer: payer,
+                    amount: share,
+                    release_at: release_day * U256::from(SECONDS_PER_DAY),
+                });
+            }
+            let earned = self.app_payee_earnings.getter(app_id).get(payee);
+            self.app_payee_earnings.setter(app_id).setter(payee).set(checked_add(earned, share)?);
+        }
+        let treasury_balance = self.balances.getter(treasury_addr).get(usdc_addr);
+        self.balances.setter(treasury_addr).setter(usdc_addr).set(checked_add(treasury_balance, platform_fee)?);
+        self.vm().log(BalanceCredited {
+            account: treasury_addr,
+            token: usdc_addr,
+            app_id,
+            amount: platform_fee,
+        });
+
+        // Update analytics
+        let current_platform_rev = self.total_platform_revenue.get();
+        self.total_platform_revenue.set(checked_add(current_platform_rev, platform_fee)?);
+        let current_dev_payouts = self.total_developer_payouts.get();
+        self.total_developer_payouts.set(checked_add(current_dev_payouts, developer_share)?);
+        let developer = self.app_developers.get(app_id);
+        let earnings = self.developer_earnings.get(developer);
+        self.developer_earnings.setter(developer).set(checked_add(earnings, developer_share)?);
+
+        Ok((developer_share, platform_fee))
+    }
+
+    /// Add a renewal payment to the app's gross and daily revenue
+    fn record_renewal_revenue(&mut self, app_id: U256, amount: U256) -> Result<(), Vec<u8>> {
+        let day = U256::from(self.__stylus_host.block_timestamp() / SECONDS_PER_DAY);
+        let gross = self.app_gross_revenue.get(app_id);
+        self.app_gross_revenue.setter(app_id).set(checked_add(gross, amount)?);
+        let daily = self.app_daily_revenue.getter(app_id).get(day);
+        self.app_daily_revenue.setter(app_id).setter(day).set(checked_add(daily, amount)?);
+        Ok(())
+    }
+
+    /// Price in effect now, including a scheduled change that is due
+    fn current_price(&self, app_id: U256) -> U256 {
+        let effective_at = self.app_pending_price_times.get(app_id);
+        if effective_at != U256::ZERO && U256::from(self.__stylus_host.block_timestamp()) >= effective_at {
+            return self.app_pending_prices.get(app_id);
+        }
+
+        self.app_prices.get(app_id)
+    }
+
+    /// Apply a scheduled price change that is due
+    fn settle_price(&mut self, app_id: U256) {
+        let effective_at = self.app_pending_price_times.get(app_id);
+        if effective_at != U256::ZERO && U256::from(self.__stylus_host.block_timestamp()) >= effective_at {
+            let price = self.app_pending_prices.get(app_id);
+            self.app_prices.setter(app_id).set(price);
+            self.app_pending_prices.setter(app_id).set(U256::ZERO);
+            self.app_pending_price_times.setter(app_id).set(U256::ZERO);
+        }
+    }
+
+    /// Record a price change in the history and apply it now or schedule it,
+    /// replacing any pending change
+    fn schedule_price(&mut self, app_id: U256, price: U256, effective_at: U256) -> Result<(), Vec<u8>> {
+        self.settle_price(app_id);
+        let now = U256::from(self.__stylus_host.block_timestamp());
+
+        // A pending change is dropped, along with its history entry
+        let mut index = self.price_history_counts.get(app_id);
+        if self.app_pending_price_times.get(app_id) != U256::ZERO {
+            index = checked_sub(index, U256::from(1))?;
+            self.app_pending_prices.setter(app_id).set(U256::ZERO);
+            self.app_pending_price_times.setter(app_id).set(U256::ZERO);
+        }
+
+        // Subscribers get notice of increases (the first listing needs none)
+        let notice_until = checked_add(now, U256::from(PRICE_NOTICE_SECONDS))?;
+        if self.app_is_subscription.get(app_id)
+            && index > U256::ZERO
+            && price > self.app_prices.get(app_id)
+            && effective_at < notice_until
+        {
+            return Err(ERROR_NOTICE_TOO_SHORT.to_vec());
+        }
+
+        let effective_at = effective_at.max(now);
+        self.price_history_prices.setter(app_id).setter(index).set(price);
+        self.price_history_times.setter(app_id).setter(index).set(effective_at);
+        self.price_history_counts.setter(app_id).set(checked_add(index, U256::from(1))?);
+
+        if effective_at == now {
+            self.app_prices.setter(app_id).set(price);
+        } else {
+            self.app_pending_prices.setter(app_id).set(price);
+            self.app_pending_price_times.setter(app_id).set(effective_at);
+        }
+
+        Ok(())
+    }
+
+    /// Platform fee for an app's sales: the app override, else the
+    /// developer override, else the volume tier reached by the developer's
+    /// lifetime earnings, else the default 10%
+    fn fee_bps(&self, app_id: U256) -> Result<u64, Vec<u8>> {
+        if self.app_fee_set.get(app_id) {
+            return to_u64(self.app_fee_bps.get(app_id));
+        }
+
+        let developer = self.app_developers.get(app_id);
+        if self.developer_fee_set.get(developer) {
+            return to_u64(self.developer_fee_bps.get(developer));
+        }
+
+        let earnings = self.developer_earnings.get(developer);
+        let count = to_u64(self.fee_tier_count.get())?;
+        (0..count)
+            .rev()
+            .map(U256::from)
+            .find(|i| self.fee_tier_thresholds.get(*i) <= earnings)
+            .map_or(Ok(PLATFORM_FEE_BPS), |i| to_u64(self.fee_tier_bps.get(i)))
+    }
+
+    /// Volume discount (bps) of the largest tier `quantity` reaches
+    fn seat_discount(&self, app_id: U256, quantity: u64) -> Result<u64, Vec<u8>> {
+        let count = to_u64(self.seat_discount_counts.get(app_id))?;
+
+        (0..count)
+            .rev()
+            .map(U256::from)
+            .find(|i| self.seat_discount_min_quantities.getter(app_id).get(*i) <= U256::from(quantity))
+            .map_or(Ok(0), |i| to_u64(self.seat_discount_bps.getter(app_id).get(i)))
+    }
+
+    /// Price of `quantity` seats after volume discount
+    fn seat_price(&self, app_id: U256, quantity: u64) -> Result<U256, Vec<u8>> {
+        let list_total = checked_mul(self.current_price(app_id), U256::from(quantity))?;
+        let discount = bps_of(list_total, self.seat_discount(app_id, quantity)?)?;
+        checked_sub(list_total, discount)
+    }
+
+    /// Move license tokens between holders (ERC-1155 transfer checks)
+    fn move_licenses(
+        &mut self,
+        operator: Address,
+        from: Address,
+        to: Address,
+        ids: Vec<U256>,
+        values: Vec<U256>,
+    ) -> Result<(), Vec<u8>> {
+        if to == Address::ZERO {
+            return Err(ERROR_INVALID_RECIPIENT.to_vec());
+        }
+        if operator != from && !self.operator_approvals.getter(from).get(operator) {
+            return Err(ERROR_UNAUTHORIZED.to_vec());
+        }
+        if ids.len() != values.len() {
+            return Err(ERROR_INVALID_BATCH.to_vec());
+        }
+
+        for (id, value) in ids.into_iter().zip(values) {
+            if !self.app_license_transferable.get(id) {
+                return Err(ERROR_NOT_TRANSFERABLE.to_vec());
+            }
+            let from_balance = self.license_balances.getter(id).get(from);
+            if from_balance < value {
+                return Err(ERROR_INSUFFICIENT_BALANCE.to_vec());
+            }
+            self.set_license_balance(id, from, from_balance - value)?;
+            let to_balance = self.license_balances.getter(id).get(to);
+            self.set_license_balance(id, to, checked_add(to_balance, value)?)?;
+
+            // A subscription license carries its paid interval
+            if self.app_is_subscription.get(id) {
+                let paid_through = self.subscription_ends_at(id, from)?.max(self.subscription_paid_through.getter(id).get(to));
+                self.set_paid_through(id, to, paid_through);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set a holder's license balance, adding the app to the holder's library
+    /// when the balance becomes non-zero and removing it (swap-and-pop) when
+    /// it drops to zero
+    fn set_license_balance(&mut self, app_id: U256, holder: Address, balance: U256) -> Result<(), Vec<u8>> {
+        self.license_balances.setter(app_id).setter(holder).set(balance);
+
+        let position = self.library_positions.getter(app_id).get(holder);
+        let count = self.library_counts.get(holder);
+        if balance > U256::ZERO && position == U256::ZERO {
+            self.library_apps.setter(holder).setter(count).set(app_id);
+            let new_count = checked_add(count, U256::from(1))?;
+            self.library_positions.setter(app_id).setter(holder).set(new_count);
+            self.library_counts.setter(holder).set(new_count);
+        } else if balance == U256::ZERO && position != U256::ZERO {
+            // Move the last entry into the removed slot
+            let last = checked_sub(count, U256::from(1))?;
+            let index = position - U256::from(1);
+            if index != last {
+                let moved = self.library_apps.getter(holder).get(last);
+                self.library_apps.setter(holder).setter(index).set(moved);
+                self.library_positions.setter(moved).setter(holder).set(position);
+            }
+            self.library_apps.setter(holder).setter(last).set(U256::ZERO);
+            self.library_positions.setter(app_id).setter(holder).set(U256::ZERO);
+            self.library_counts.setter(holder).set(last);
+        }
+
+        Ok(())
+    }
+
+    /// Require a contract recipient of a license transfer to accept it
+    fn check_license_receiver(&mut self, to: Address, calldata: Vec<u8>, selector: FixedBytes<4>) -> Result<(), Vec<u8>> {
+        if self.vm().code_size(to) == 0 {
+            return Ok(());
+        }
+
+        let result = unsafe { RawCall::new(&self.__stylus_host).call(to, &calldata) };
+        match result {
+            Ok(data) if data.len() >= 4 && data[..4] == selector[..] => Ok(()),
+            _ => Err(ERROR_INVALID_RECEIVER.to_vec()),
+        }
+    }
+
+    /// Transfer ERC-20 USDC from one address to another via transferFrom
+    /// Requires the `from` address to have approved this contract
+    fn usdc_transfer_from(&self, from: Address, to: Address, amount: U256) -> Result<(), Vec<u8>> {
+        let usdc_addr = Address::from_slice(&USDC_ADDRESS);
+
+        // Encode transferFrom(from, to, amount)
+        let calldata = transferFromCall {
+            from,
+            to,
+            amount,
+        }.abi_encode();
+
+        // Call USDC contract
+        unsafe {
+            RawCall::new(&self.__stylus_host)
+                .call(usdc_addr, &calldata)
+                .map_err(|_| ERROR_TRANSFER_FAILED.to_vec())?;
+        }
+
+        Ok(())
+    }
+
+    /// Apply an EIP-2612 USDC permit from `owner` to this contract
+    ///
+    /// A failed permit is tolerated if the allowance already covers `amount`,
+    /// so a front-run or reused permit does not block the payment.
+    fn usdc_permit(
+        &mut self,
+        owner: Address,
+        amount: U256,
+        deadline: U256,
+        v: u8,
+        r: B256,
+        s: B256,
+    ) -> Result<(), Vec<u8>> {
+        let usdc_addr = Address::from_slice(&USDC_ADDRESS);
+        let spender = self.vm().contract_address();
+
+        let calldata = permitCall {
+            owner,
+            spender,
+            value: amount,
+            deadline,
+            v,
+            r,
+            s,
+        }.abi_encode();
+
+        let permitted = unsafe {
+            RawCall::new(&self.__stylus_host)
+                .call(usdc_addr, &calldata)
+                .is_ok()
+        };
+        if permitted {
+            return Ok(());
+        }
+
+        // Fall back to an existing allowance
+        let calldata = allowanceCall { owner, spender }.abi_encode();
+        let output = unsafe {
+            RawCall::new_static(&self.__stylus_host)
+                .call(usdc_addr, &calldata)
+                .map_err(|_| ERROR_PERMIT_FAILED.to_vec())?
+        };
+        let allowance = allowanceCall::abi_decode_returns(&output)
+            .map_err(|_| ERROR_PERMIT_FAILED.to_vec())?;
+        if allowance < amount {
+            return Err(ERROR_PERMIT_FAILED.to_vec());
+        }
+
+        Ok(())
+    }
+
+    /// Transfer ERC-20 tokens held by this contract to another address
+    fn token_transfer(&self, token: Address, to: Address, amount: U256) -> Result<(), Vec<u8>> {
+        // Encode transfer(to, amount)
+        let calldata = transferCall {
+            to,
+            amount,
+        }.abi_encode();
+
+        // Call token contract
+        unsafe {
+            RawCall::new(&self.__stylus_host)
+                .call(token, &calldata)
+                .map_err(|_| ERROR_TRANSFER_FAILED.to_vec())?;
+        }
+
+        Ok(())
+    }
+
+    /// Debit `amount` from an account's ledger balance and transfer it to `to`
+    fn withdraw_balance(
+        &mut self,
+        account: Address,
+        token: Address,
+        amount: U256,
+        to: Address,
+    ) -> Result<(), Vec<u8>> {
+        if to == Address::ZERO {
+            return Err(ERROR_INVALID_RECIPIENT.to_vec());
+        }
+
+        let balance = self.balances.getter(account).get(token);
+        if amount == U256::ZERO || amount > balance {
+            return Err(ERROR_INSUFFICIENT_BALANCE.to_vec());
+        }
+
+        // === Effects BEFORE interactions ===
+        self.balances.setter(account).setter(token).set(checked_sub(balance, amount)?);
+
+        // === Interaction: ERC-20 transfer (a failed payout reverts the debit) ===
+        self.token_transfer(token, to, amount)?;
+
+        self.vm().log(Withdrawal {
+            account,
+            token,
+            to,
+            amount,
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stylus_sdk::testing::*;
+
+    const APP_ID: u64 = 1;
+    const PRICE: U256 = U256::from_limbs([99_000_000, 0, 0, 0]);
+    const DEVELOPER_SHARE: U256 = U256::from_limbs([89_100_000, 0, 0, 0]);
+    const PLATFORM_FEE: U256 = U256::from_limbs([9_900_000, 0, 0, 0]);
+
+    // Default tier IDs, prices and quotas (see DEFAULT_TIERS)
+    const TIER_STARTER: u64 = 1;
+    const TIER_GROWTH: u64 = 2;
+    const TIER_ENTERPRISE: u64 = 3;
+    const TIER_STARTER_PRICE: u64 = DEFAULT_TIERS[1].1;
+    const TIER_GROWTH_PRICE: u64 = DEFAULT_TIERS[2].1;
+    const TIER_ENTERPRISE_PRICE: u64 = DEFAULT_TIERS[3].1;
+    const TIER_STARTER_QUOTA: u64 = DEFAULT_TIERS[1].2;
+
+    fn mock_address(seed: u8) -> Address {
+        Address::from([seed; 20])
+    }
+
+    fn usdc() -> Address {
+        Address::from_slice(&USDC_ADDRESS)
+    }
+
+    fn treasury() -> Address {
+        Address::from_slice(&TREASURY)
+    }
+
+    /// Deploy the contract and list APP_ID for sale by `developer`
+    fn setup(vm: &TestVM, developer: Address) -> VarityPayments {
+        let mut contract = VarityPayments::from(vm);
+        vm.set_sender(developer);
+        contract.initialize().unwrap();
+        contract.set_app_price(APP_ID, PRICE, false, 0).unwrap();
+        contract
+    }
+
+    /// Deploy the contract and list APP_ID as a 30-day subscription by `developer`
+    fn setup_subscription(vm: &TestVM, developer: Address) -> VarityPayments {
+        let mut contract = VarityPayments::from(vm);
+        vm.set_sender(developer);
+        contract.initialize().unwrap();
+        contract.set_app_price(APP_ID, PRICE, true, 30).unwrap();
+        contract
+    }
+
+    /// Mock a USDC call with the given result
+    fn mock_usdc(vm: &TestVM, calldata: Vec<u8>, result: Result<Vec<u8>, Vec<u8>>) {
+        vm.mock_call(usdc(), calldata, U256::ZERO, result);
+    }
+
+    fn pull_calldata(vm: &TestVM, buyer: Address) -> Vec<u8> {
+        transferFromCall {
+            from: buyer,
+            to: vm.contract_address(),
+            amount: PRICE,
+        }.abi_encode()
+    }
+
+    fn payout_calldata(to: Address, amount: U256) -> Vec<u8> {
+        transferCall { to, amount }.abi_encode()
+    }
+
+    /// Run a contract call as a transaction: TestVM keeps storage written
+    /// before an error, so roll it back the way the EVM reverts it
+    fn transact<T>(vm: &TestVM, call: impl FnOnce() -> Result<T, Vec<u8>>) -> Result<T, Vec<u8>> {
+        let storage = vm.snapshot().storage;
+        let result = call();
+        if result.is_err() {
+            vm.clear_storage();
+            for (key, value) in storage {
+                vm.set_storage(key, value);
+            }
+        }
+        result
+    }
+
+    /// Purchase APP_ID as `buyer` with all USDC calls succeeding
+    fn purchase(vm: &TestVM, contract: &mut VarityPayments, buyer: Address) {
+        vm.set_sender(buyer);
+        contract.purchase_app(APP_ID, PRICE).unwrap();
+    }
+
+    #[test]
+    fn test_purchase_pulls_full_price_and_credits_ledger() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let mut contract = setup(&vm, developer);
+
+        mock_usdc(&vm, pull_calldata(&vm, buyer), Ok(U256::from(1).to_be_bytes::<32>().to_vec()));
+        purchase(&vm, &mut contract, buyer);
+
+        assert!(contract.has_user_purchased(APP_ID, buyer).unwrap());
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), DEVELOPER_SHARE);
+        assert_eq!(contract.get_balance(treasury(), usdc()).unwrap(), PLATFORM_FEE);
+        assert_eq!(contract.get_total_platform_revenue().unwrap(), PLATFORM_FEE);
+        assert_eq!(contract.get_total_developer_payouts().unwrap(), DEVELOPER_SHARE);
+        // 4x TierUpdated + AppPriceSet + 2x BalanceCredited + TransferSingle + AppPurchased
+        assert_eq!(vm.get_emitted_logs().len(), 9);
+    }
+
+    #[test]
+    fn test_purchase_reverts_when_pull_fails() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let mut contract = setup(&vm, developer);
+
+        mock_usdc(&vm, pull_calldata(&vm, buyer), Err(b"insufficient allowance".to_vec()));
+
+        vm.set_sender(buyer);
+        let result = transact(&vm, || contract.purchase_app(APP_ID, PRICE));
+
+        assert_eq!(result, Err(ERROR_TRANSFER_FAILED.to_vec()));
+        assert!(!contract.has_user_purchased(APP_ID, buyer).unwrap());
+        assert_eq!(contract.balance_of(buyer, U256::from(APP_ID)).unwrap(), U256::ZERO);
+        assert_eq!(contract.get_library_count(buyer).unwrap(), 0);
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), U256::ZERO);
+        assert_eq!(contract.get_balance(treasury(), usdc()).unwrap(), U256::ZERO);
+        assert_eq!(contract.get_total_platform_revenue().unwrap(), U256::ZERO);
+        assert_eq!(contract.get_total_developer_payouts().unwrap(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_blocked_developer_does_not_block_purchases() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let mut contract = setup(&vm, developer);
+
+        // Developer address cannot receive USDC (e.g., blacklisted)
+        mock_usdc(&vm, payout_calldata(developer, DEVELOPER_SHARE), Err(b"blacklisted".to_vec()));
+
+        purchase(&vm, &mut contract, mock_address(2));
+
+        // Withdrawal to the blocked address reverts and leaves the balance intact
+        vm.set_sender(developer);
+        let result = transact(&vm, || contract.withdraw_all(usdc(), developer));
+        assert_eq!(result, Err(ERROR_TRANSFER_FAILED.to_vec()));
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), DEVELOPER_SHARE);
+
+        // Developer can withdraw to another address instead
+        let alternate = mock_address(3);
+        contract.withdraw_all(usdc(), alternate).unwrap();
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_set_app_price_developer_only() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let mut contract = setup(&vm, developer);
+
+        vm.set_sender(mock_address(2));
+        let result = contract.set_app_price(APP_ID, U256::from(1), false, 0);
+        assert_eq!(result, Err(ERROR_UNAUTHORIZED.to_vec()));
+        assert_eq!(contract.get_app_pricing(APP_ID).unwrap().1, developer);
+    }
+
+    #[test]
+    fn test_set_app_price_rejects_relisting_subscription_increase() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let mut contract = setup_subscription(&vm, developer);
+        let new_price = PRICE * U256::from(2);
+
+        // An increase cannot skip the notice period by relisting
+        vm.set_block_timestamp(1_000);
+        assert_eq!(contract.set_app_price(APP_ID, new_price, true, 30), Err(ERROR_PRICING_SET.to_vec()));
+        assert_eq!(contract.update_app_price(APP_ID, new_price), Err(ERROR_NOTICE_TOO_SHORT.to_vec()));
+        assert_eq!(contract.get_app_pricing(APP_ID).unwrap().0, PRICE);
+    }
+
+    #[test]
+    fn test_set_app_price_keeps_lifetime_licenses_lifetime() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let mut contract = setup(&vm, developer);
+        vm.set_block_timestamp(1_000);
+        purchase(&vm, &mut contract, buyer);
+
+        // The billing model cannot be switched to a subscription
+        vm.set_sender(developer);
+        assert_eq!(contract.set_app_price(APP_ID, PRICE, true, 30), Err(ERROR_PRICING_SET.to_vec()));
+        vm.set_block_timestamp(1_000 + 31 * SECONDS_PER_DAY);
+        assert!(!contract.get_app_pricing(APP_ID).unwrap().2);
+        assert!(contract.has_access(APP_ID, buyer).unwrap());
+    }
+
+    #[test]
+    fn test_withdraw_partial_amount() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let mut contract = setup(&vm, developer);
+        purchase(&vm, &mut contract, mock_address(2));
+
+        vm.set_sender(developer);
+        contract.withdraw(usdc(), U256::from(1_000_000), developer).unwrap();
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), DEVELOPER_SHARE - U256::from(1_000_000));
+
+        let result = contract.withdraw(usdc(), DEVELOPER_SHARE, developer);
+        assert_eq!(result, Err(ERROR_INSUFFICIENT_BALANCE.to_vec()));
+
+        let result = contract.withdraw(usdc(), U256::from(1), Address::ZERO);
+        assert_eq!(result, Err(ERROR_INVALID_RECIPIENT.to_vec()));
+    }
+
+    #[test]
+    fn test_sweep_treasury_owner_only() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let mut contract = setup(&vm, developer); // developer deployed, so is owner
+        purchase(&vm, &mut contract, mock_address(2));
+
+        // Failed sweep reverts and keeps the fees on the ledger
+        mock_usdc(&vm, payout_calldata(treasury(), PLATFORM_FEE), Err(b"paused".to_vec()));
+        vm.set_sender(developer);
+        let result = transact(&vm, || contract.sweep_treasury(usdc()));
+        assert_eq!(result, Err(ERROR_TRANSFER_FAILED.to_vec()));
+        assert_eq!(contract.get_balance(treasury(), usdc()).unwrap(), PLATFORM_FEE);
+
+        vm.clear_mocks();
+        vm.set_sender(mock_address(2));
+        assert_eq!(contract.sweep_treasury(usdc()), Err(ERROR_UNAUTHORIZED.to_vec()));
+
+        vm.set_sender(developer);
+        contract.sweep_treasury(usdc()).unwrap();
+        assert_eq!(contract.get_balance(treasury(), usdc()).unwrap(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_refund_within_window_claws_back_split() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let mut contract = setup(&vm, developer);
+        contract.set_refund_window(APP_ID, 14).unwrap();
+
+        vm.set_block_timestamp(1_000);
+        purchase(&vm, &mut contract, buyer);
+
+        vm.set_block_timestamp(1_000 + 13 * SECONDS_PER_DAY);
+        vm.set_sender(developer);
+        contract.refund_purchase(APP_ID, buyer).unwrap();
+
+        assert!(!contract.has_user_purchased(APP_ID, buyer).unwrap());
+        assert_eq!(contract.get_balance(buyer, usdc()).unwrap(), PRICE);
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), U256::ZERO);
+        assert_eq!(contract.get_balance(treasury(), usdc()).unwrap(), U256::ZERO);
+        assert_eq!(contract.get_total_platform_revenue().unwrap(), U256::ZERO);
+        assert_eq!(contract.get_total_developer_payouts().unwrap(), U256::ZERO);
+        assert_eq!(contract.get_purchase_record(APP_ID, buyer).unwrap(), (0, U256::ZERO, U256::ZERO));
+    }
+
+    #[test]
+    fn test_refund_requires_developer_or_admin() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let admin = mock_address(3);
+        let mut contract = setup(&vm, developer);
+        contract.set_refund_window(APP_ID, 7).unwrap();
+        contract.set_admin(admin, true).unwrap();
+        purchase(&vm, &mut contract, buyer);
+
+        vm.set_sender(buyer);
+        assert_eq!(contract.refund_purchase(APP_ID, buyer), Err(ERROR_UNAUTHORIZED.to_vec()));
+
+        vm.set_sender(admin);
+        contract.refund_purchase(APP_ID, buyer).unwrap();
+        assert_eq!(contract.refund_purchase(APP_ID, buyer), Err(ERROR_NOT_PURCHASED.to_vec()));
+    }
+
+    #[test]
+    fn test_refund_rejected_outside_window() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let mut contract = setup(&vm, developer);
+        purchase(&vm, &mut contract, buyer);
+
+        // No refund window configured
+        vm.set_sender(developer);
+        let result = contract.refund_purchase(APP_ID, buyer);
+        assert_eq!(result, Err(ERROR_REFUND_WINDOW_CLOSED.to_vec()));
+
+        // Window configured but expired
+        contract.set_refund_window(APP_ID, 1).unwrap();
+        vm.set_block_timestamp(vm.block_timestamp() + SECONDS_PER_DAY + 1);
+        let result = contract.refund_purchase(APP_ID, buyer);
+        assert_eq!(result, Err(ERROR_REFUND_WINDOW_CLOSED.to_vec()));
+        assert!(contract.has_user_purchased(APP_ID, buyer).unwrap());
+    }
+
+    #[test]
+    fn test_refund_fails_after_developer_withdrawal() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let mut contract = setup(&vm, developer);
+        contract.set_refund_window(APP_ID, 7).unwrap();
+        purchase(&vm, &mut contract, buyer);
+
+        vm.set_sender(developer);
+        contract.withdraw_all(usdc(), developer).unwrap();
+
+        let result = contract.refund_purchase(APP_ID, buyer);
+        assert_eq!(result, Err(ERROR_INSUFFICIENT_BALANCE.to_vec()));
+        assert!(contract.has_user_purchased(APP_ID, buyer).unwrap());
+    }
+
+    #[test]
+    fn test_purchase_for_recipient() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let payer = mock_address(2);
+        let recipient = mock_address(3);
+        let mut contract = setup(&vm, developer);
+        contract.set_refund_window(APP_ID, 7).unwrap();
+
+        // Only the payer's allowance is charged
+        mock_usdc(&vm, pull_calldata(&vm, recipient), Err(b"no allowance".to_vec()));
+        vm.set_sender(payer);
+        contract.purchase_app_for(APP_ID, recipient, PRICE).unwrap();
+
+        assert!(contract.has_user_purchased(APP_ID, recipient).unwrap());
+        assert!(!contract.has_user_purchased(APP_ID, payer).unwrap());
+        assert_eq!(
+            contract.purchase_app_for(APP_ID, recipient, PRICE),
+            Err(ERROR_ALREADY_PURCHASED.to_vec())
+        );
+
+        // Refunds are credited to the payer, not the recipient
+        vm.set_sender(developer);
+        contract.refund_purchase(APP_ID, recipient).unwrap();
+        assert_eq!(contract.get_balance(payer, usdc()).unwrap(), PRICE);
+        assert_eq!(contract.get_balance(recipient, usdc()).unwrap(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_purchase_for_zero_address_rejected() {
+        let vm = TestVM::default();
+        let mut contract = setup(&vm, mock_address(1));
+
+        vm.set_sender(mock_address(2));
+        let result = contract.purchase_app_for(APP_ID, Address::ZERO, PRICE);
+        assert_eq!(result, Err(ERROR_INVALID_RECIPIENT.to_vec()));
+    }
+
+    #[test]
+    fn test_revenue_analytics_track_purchases_and_refunds() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let mut contract = setup(&vm, developer);
+        contract.set_refund_window(APP_ID, 7).unwrap();
+
+        let day = 20_000;
+        vm.set_block_timestamp(day * SECONDS_PER_DAY);
+        purchase(&vm, &mut contract, mock_address(2));
+        vm.set_block_timestamp((day + 1) * SECONDS_PER_DAY);
+        purchase(&vm, &mut contract, mock_address(3));
+
+        assert_eq!(contract.get_app_gross_revenue(APP_ID).unwrap(), PRICE * U256::from(2));
+        assert_eq!(
+            contract.get_developer_earnings(developer).unwrap(),
+            DEVELOPER_SHARE * U256::from(2)
+        );
+        assert_eq!(contract.get_app_purchase_count(APP_ID).unwrap(), U256::from(2));
+        assert_eq!(contract.get_app_lifetime_buyers(APP_ID).unwrap(), U256::from(2));
+        assert_eq!(
+            contract.get_app_revenue_for_days(APP_ID, day, day + 1).unwrap(),
+            PRICE * U256::from(2)
+        );
+
+        // Refund is removed from the bucket of the original purchase day
+        vm.set_sender(developer);
+        contract.refund_purchase(APP_ID, mock_address(2)).unwrap();
+        assert_eq!(contract.get_app_revenue_for_days(APP_ID, day, day).unwrap(), U256::ZERO);
+        assert_eq!(
+            contract.get_app_revenue_for_days(APP_ID, day + 1, day + 1).unwrap(),
+            PRICE
+        );
+        assert_eq!(contract.get_app_purchase_count(APP_ID).unwrap(), U256::from(1));
+        assert_eq!(contract.get_developer_earnings(developer).unwrap(), DEVELOPER_SHARE);
+
+        let result = contract.get_app_revenue_for_days(APP_ID, day, day + MAX_REVENUE_RANGE_DAYS);
+        assert_eq!(result, Err(ERROR_INVALID_PERIOD.to_vec()));
+    }
+
+    #[test]
+    fn test_large_amounts_use_full_u256_range() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let mut contract = VarityPayments::from(&vm);
+        vm.set_sender(developer);
+        contract.initialize().unwrap();
+
+        // 1 billion tokens with 18 decimals does not fit in u64
+        let price = U256::from(1_000_000_000u64) * U256::from(10u64).pow(U256::from(18));
+        contract.set_app_price(APP_ID, price, false, 0).unwrap();
+        assert_eq!(contract.get_app_pricing(APP_ID).unwrap().0, price);
+
+        vm.set_sender(buyer);
+        contract.purchase_app(APP_ID, price).unwrap();
+        assert_eq!(contract.get_balance(treasury(), usdc()).unwrap(), price / U256::from(10));
+        assert_eq!(contract.get_total_developer_payouts().unwrap(), price - price / U256::from(10));
+    }
+
+    #[test]
+    fn test_fee_overflow_returns_typed_error() {
+        let vm = TestVM::default();
+        let mut contract = setup(&vm, mock_address(1));
+        contract.update_app_price(APP_ID, U256::MAX).unwrap();
+
+        vm.set_sender(mock_address(2));
+        let result = contract.purchase_app(APP_ID, U256::MAX);
+        assert_eq!(result, Err(ERROR_ARITHMETIC_OVERFLOW.to_vec()));
+        assert!(!contract.has_user_purchased(APP_ID, mock_address(2)).unwrap());
+    }
+
+    #[test]
+    fn test_invoice_partial_and_full_payment() {
+        let vm = TestVM::default();
+        let owner = mock_address(1);
+        let biller = mock_address(4);
+        let developer = mock_address(5);
+        let mut contract = setup(&vm, owner);
+        contract.set_biller(biller, true).unwrap();
+
+        vm.set_block_timestamp(1_000);
+        vm.set_sender(biller);
+        let invoice_id = contract
+            .issue_invoice(APP_ID, developer, 202602, TIER_GROWTH, U256::ZERO, 2_000)
+            .unwrap();
+        assert_eq!(invoice_id, 1);
+        assert_eq!(contract.get_app_invoices(APP_ID).unwrap(), vec![1]);
+
+        let tier_price = U256::from(TIER_GROWTH_PRICE);
+        let half = tier_price / U256::from(2);
+        vm.set_sender(developer);
+        contract.pay_invoice(invoice_id, half).unwrap();
+
+        let invoice = contract.get_invoice(invoice_id).unwrap();
+        assert_eq!(invoice.4, tier_price);
+        assert_eq!(invoice.5, half);
+        assert!(!invoice.7); // not paid
+        assert!(!invoice.8); // not overdue yet
+
+        // Remaining balance becomes overdue after the due date
+        vm.set_block_timestamp(2_001);
+        assert!(contract.get_invoice(invoice_id).unwrap().8);
+
+        let result = contract.pay_invoice(invoice_id, tier_price);
+        assert_eq!(result, Err(ERROR_INVOICE_OVERPAYMENT.to_vec()));
+
+        contract.pay_invoice(invoice_id, tier_price - half).unwrap();
+        let invoice = contract.get_invoice(invoice_id).unwrap();
+        assert!(invoice.7);
+        assert!(!invoice.8);
+        assert_eq!(contract.get_billing_payment(APP_ID, 202602).unwrap(), tier_price);
+    }
+
+    #[test]
+    fn test_issue_invoice_requires_billing_role() {
+        let vm = TestVM::default();
+        let mut contract = setup(&vm, mock_address(1));
+
+        vm.set_sender(mock_address(2));
+        let result = contract.issue_invoice(APP_ID, mock_address(2), 1, TIER_STARTER, U256::ZERO, 0);
+        assert_eq!(result, Err(ERROR_UNAUTHORIZED.to_vec()));
+
+        vm.set_sender(mock_address(1));
+        let result = contract.issue_invoice(APP_ID, mock_address(2), 1, 9, U256::ZERO, 0);
+        assert_eq!(result, Err(ERROR_INVALID_TIER.to_vec()));
+        assert_eq!(contract.pay_invoice(1, PRICE), Err(ERROR_INVOICE_NOT_FOUND.to_vec()));
+    }
+
+    #[test]
+    fn test_prorated_invoice_across_tier_change() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let mut contract = setup(&vm, developer);
 
-        // Check pricing is active
-        if !self.app_pricing_active.get(app_id_u256) {
-            return Err(ERROR_APP_NOT_FOR_SALE.to_vec());
-        }
+        // Starter for the first quarter of the period, growth for the rest
+        contract.record_tier_change(APP_ID, TIER_STARTER, 0).unwrap();
+        contract.record_tier_change(APP_ID, TIER_GROWTH, 250).unwrap();
 
-        let price = self.app_prices.get(app_id_u256);
-        if price == U256::ZERO {
-            return Err(ERROR_APP_NOT_FOR_SALE.to_vec());
-        }
+        let expected = U256::from(TIER_STARTER_PRICE / 4 + TIER_GROWTH_PRICE * 3 / 4);
+        assert_eq!(contract.get_prorated_amount(APP_ID, 0, 1_000).unwrap(), expected);
+        assert_eq!(contract.get_app_tier_at(APP_ID, 999).unwrap(), TIER_GROWTH);
 
-        // Check not already purchased
-        if self.has_purchased.getter(app_id_u256).get(buyer) {
-            return Err(ERROR_ALREADY_PURCHASED.to_vec());
-        }
+        let invoice_id = contract
+            .issue_prorated_invoice(APP_ID, developer, 202603, 0, 1_000, 2_000)
+            .unwrap();
 
-        // Calculate split (90% developer, 10% platform)
-        let price_u64 = price.to::<u64>();
-        let platform_fee = (price_u64 * PLATFORM_FEE_BPS) / BPS_DENOMINATOR;
-        let developer_share = price_u64 - platform_fee;
+        // No tier recorded at the period start: the history is incomplete
+        contract.record_tier_change(2, TIER_GROWTH, 500).unwrap();
+        assert_eq!(
+            contract.issue_prorated_invoice(2, developer, 202603, 0, 1_000, 2_000),
+            Err(ERROR_INVALID_TIER.to_vec())
+        );
+        let (_, _, _, tier, amount, _, _, _, _) = contract.get_invoice(invoice_id).unwrap();
+        assert_eq!(tier, TIER_GROWTH);
+        assert_eq!(amount, expected);
 
-        let developer = self.app_developers.get(app_id_u256);
-        let treasury_addr = Address::from_slice(&TREASURY);
+        // Changes must be recorded in order
+        assert_eq!(
+            contract.record_tier_change(APP_ID, TIER_STARTER, 100),
+            Err(ERROR_INVALID_PERIOD.to_vec())
+        );
+        assert_eq!(
+            contract.get_prorated_amount(APP_ID, 1_000, 1_000),
+            Err(ERROR_INVALID_PERIOD.to_vec())
+        );
+    }
 
-        // === Effects BEFORE interactions (checks-effects-interactions pattern) ===
+    #[test]
+    fn test_tier_catalog_owner_managed() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let mut contract = setup(&vm, developer);
 
-        // Mark as purchased
-        self.has_purchased.setter(app_id_u256).setter(buyer).set(true);
+        assert_eq!(contract.get_tier_count().unwrap(), 4);
+        let new_price = U256::from(59_000_000u64);
+        contract.set_tier(TIER_STARTER, "starter".to_string(), new_price, 60_000, true).unwrap();
+        assert_eq!(contract.get_tier_price(TIER_STARTER).unwrap(), new_price);
 
-        // Update analytics
-        let current_platform_rev = self.total_platform_revenue.get();
-        self.total_platform_revenue.set(current_platform_rev + U256::from(platform_fee));
+        // Append a new tier, then retire it
+        contract.set_tier(4, "scale".to_string(), U256::from(499_000_000u64), 5_000_000, true).unwrap();
+        assert_eq!(contract.get_tier_count().unwrap(), 5);
+        contract.set_tier(4, "scale".to_string(), U256::from(499_000_000u64), 5_000_000, false).unwrap();
+        assert_eq!(contract.get_tier_price(4), Err(ERROR_INVALID_TIER.to_vec()));
+        assert_eq!(
+            contract.record_tier_change(APP_ID, 4, 0),
+            Err(ERROR_INVALID_TIER.to_vec())
+        );
 
-        let current_dev_payouts = self.total_developer_payouts.get();
-        self.total_developer_payouts.set(current_dev_payouts + U256::from(developer_share));
+        // Gaps in tier IDs, duplicate, renamed or oversized names and non-owners are rejected
+        assert_eq!(
+            contract.set_tier(9, "gap".to_string(), U256::ZERO, 0, true),
+            Err(ERROR_INVALID_TIER.to_vec())
+        );
+        assert_eq!(
+            contract.set_tier(5, "growth".to_string(), U256::ZERO, 0, true),
+            Err(ERROR_INVALID_TIER.to_vec())
+        );
+        assert_eq!(
+            contract.set_tier(TIER_STARTER, "basic".to_string(), new_price, 60_000, true),
+            Err(ERROR_INVALID_TIER.to_vec())
+        );
+        assert_eq!(
+            contract.set_tier(5, "x".repeat(33), U256::ZERO, 0, true),
+            Err(ERROR_INVALID_INPUT.to_vec())
+        );
+        assert_eq!(contract.seed_default_tiers(), Err(ERROR_TIERS_SEEDED.to_vec()));
+        vm.set_sender(mock_address(2));
+        assert_eq!(
+            contract.set_tier(TIER_STARTER, "starter".to_string(), U256::ZERO, 0, true),
+            Err(ERROR_UNAUTHORIZED.to_vec())
+        );
+    }
 
-        // === Interactions: ERC-20 USDC transfers ===
+    #[test]
+    fn test_prepaid_credit_draw_and_withdraw() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let mut contract = setup(&vm, developer);
+        let deposit = U256::from(150_000_000u64);
+        let bill = U256::from(TIER_STARTER_PRICE);
 
-        // Transfer USDC from buyer to developer (90%)
-        self.usdc_transfer_from(buyer, developer, U256::from(developer_share))?;
+        contract.deposit_credit(deposit).unwrap();
+        contract.set_credit_threshold(U256::from(TIER_ENTERPRISE_PRICE)).unwrap();
 
-        // Transfer USDC from buyer to treasury (10%)
-        self.usdc_transfer_from(buyer, treasury_addr, U256::from(platform_fee))?;
+        // Billing role draws the invoiced bill without a developer transaction
+        let biller = mock_address(3);
+        contract.set_biller(biller, true).unwrap();
+        vm.set_sender(biller);
+        let invoice_id = contract
+            .issue_invoice(APP_ID, developer, 202602, TIER_STARTER, U256::ZERO, 2_000)
+            .unwrap();
+        assert_eq!(
+            contract.draw_credit(mock_address(4), invoice_id, bill),
+            Err(ERROR_INVOICE_DEVELOPER_MISMATCH.to_vec())
+        );
+        assert_eq!(contract.draw_credit(developer, 99, bill), Err(ERROR_INVOICE_NOT_FOUND.to_vec()));
+        assert_eq!(
+            contract.draw_credit(developer, invoice_id, bill + U256::from(1)),
+            Err(ERROR_INVOICE_OVERPAYMENT.to_vec())
+        );
+        let logs_before = vm.get_emitted_logs().len();
+        contract.draw_credit(developer, invoice_id, bill).unwrap();
+        // BalanceCredited + CreditDrawn + BillingPayment + CreditLowBalance + InvoicePaid
+        assert_eq!(vm.get_emitted_logs().len() - logs_before, 5);
 
-        // Emit event
-        self.vm().log(AppPurchased {
-            app_id: app_id_u256,
-            buyer,
-            developer,
-            total_amount: price,
-            developer_share: U256::from(developer_share),
-            platform_fee: U256::from(platform_fee),
-            timestamp: U256::from(self.__stylus_host.block_timestamp()),
-        });
+        let remaining = deposit - bill;
+        assert_eq!(contract.get_credit(developer).unwrap().0, remaining);
+        assert_eq!(contract.get_billing_payment(APP_ID, 202602).unwrap(), bill);
+        assert_eq!(contract.get_balance(treasury(), usdc()).unwrap(), bill);
+        assert!(contract.get_invoice(invoice_id).unwrap().7);
+        assert_eq!(contract.draw_credit(developer, invoice_id, bill), Err(ERROR_INVOICE_OVERPAYMENT.to_vec()));
 
-        Ok(())
+        let invoice_id = contract
+            .issue_invoice(APP_ID, developer, 202603, TIER_ENTERPRISE, U256::ZERO, 2_000)
+            .unwrap();
+        assert_eq!(
+            contract.draw_credit(developer, invoice_id, U256::from(TIER_ENTERPRISE_PRICE)),
+            Err(ERROR_INSUFFICIENT_BALANCE.to_vec())
+        );
+
+        // Developer withdraws the unused credit
+        vm.set_sender(developer);
+        contract.withdraw_credit(remaining, developer).unwrap();
+        assert_eq!(contract.get_credit(developer).unwrap().0, U256::ZERO);
     }
 
-    // ============ Billing Functions ============
+    #[test]
+    fn test_failed_credit_transfers_revert() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let mut contract = setup(&vm, developer);
+        let deposit = U256::from(150_000_000u64);
+        contract.deposit_credit(deposit).unwrap();
 
-    /// Pay monthly bill for developer infrastructure — 100% to Varity treasury
-    ///
-    /// Uses ERC-20 USDC transferFrom. Developer must approve() this contract
-    /// for the bill amount before calling.
-    ///
-    /// # Arguments
-    /// * `app_id` - The app ID being billed for
-    /// * `period_hash` - Hash of billing period (e.g., keccak256("2026-02") truncated to u64)
-    /// * `amount` - Bill amount in USDC (6 decimals, e.g., 49_000_000 = $49)
-    pub fn pay_bill(&mut self, app_id: u64, period_hash: u64, amount: u64) -> Result<(), Vec<u8>> {
-        if app_id == 0 {
-            return Err(ERROR_INVALID_APP_ID.to_vec());
-        }
-        if period_hash == 0 {
-            return Err(ERROR_INVALID_PERIOD.to_vec());
-        }
-        if amount == 0 {
-            return Err(ERROR_INSUFFICIENT_PAYMENT.to_vec());
-        }
+        let pull = transferFromCall { from: developer, to: vm.contract_address(), amount: deposit }.abi_encode();
+        mock_usdc(&vm, pull, Err(b"insufficient allowance".to_vec()));
+        let result = transact(&vm, || contract.deposit_credit(deposit));
+        assert_eq!(result, Err(ERROR_TRANSFER_FAILED.to_vec()));
+        assert_eq!(contract.get_credit(developer).unwrap().0, deposit);
 
-        let app_id_u256 = U256::from(app_id);
-        let period_u256 = U256::from(period_hash);
-        let payment = U256::from(amount);
-        let developer = self.__stylus_host.msg_sender();
-        let treasury_addr = Address::from_slice(&TREASURY);
+        mock_usdc(&vm, payout_calldata(developer, deposit), Err(b"blacklisted".to_vec()));
+        let result = transact(&vm, || contract.withdraw_credit(deposit, developer));
+        assert_eq!(result, Err(ERROR_TRANSFER_FAILED.to_vec()));
+        assert_eq!(contract.get_credit(developer).unwrap().0, deposit);
+    }
 
-        // === Effects BEFORE interactions ===
+    #[test]
+    fn test_invoice_paid_from_credit() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let mut contract = setup(&vm, developer);
 
-        // Record payment
-        let current_payment = self.billing_payments.getter(app_id_u256).get(period_u256);
-        self.billing_payments
-            .setter(app_id_u256)
-            .setter(period_u256)
-            .set(current_payment + payment);
+        contract.deposit_credit(U256::from(TIER_ENTERPRISE_PRICE)).unwrap();
+        let invoice_id = contract
+            .issue_invoice(APP_ID, developer, 202602, TIER_GROWTH, U256::ZERO, 2_000)
+            .unwrap();
+        contract.pay_invoice_from_credit(invoice_id).unwrap();
 
-        // Update analytics
-        let current_platform_rev = self.total_platform_revenue.get();
-        self.total_platform_revenue.set(current_platform_rev + payment);
+        let (_, _, _, _, _, amount_paid, _, is_paid, _) = contract.get_invoice(invoice_id).unwrap();
+        assert!(is_paid);
+        assert_eq!(amount_paid, U256::from(TIER_GROWTH_PRICE));
+        assert_eq!(
+            contract.get_credit(developer).unwrap().0,
+            U256::from(TIER_ENTERPRISE_PRICE - TIER_GROWTH_PRICE)
+        );
+        assert_eq!(
+            contract.pay_invoice_from_credit(invoice_id),
+            Err(ERROR_INVOICE_OVERPAYMENT.to_vec())
+        );
+    }
 
-        // === Interaction: ERC-20 USDC transfer ===
+    #[test]
+    fn test_metered_overage_priced_into_invoice() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let reporter = mock_address(4);
+        let mut contract = setup(&vm, developer);
+        contract.set_reporter(reporter, true).unwrap();
+        // $0.001 per transaction over quota
+        contract.set_overage_rate(U256::from(1_000u64)).unwrap();
 
-        // Transfer USDC from developer to treasury (100%)
-        self.usdc_transfer_from(developer, treasury_addr, payment)?;
+        vm.set_sender(reporter);
+        contract.report_usage(APP_ID, 202602, TIER_STARTER_QUOTA - 1).unwrap();
+        assert_eq!(contract.get_overage_amount(APP_ID, 202602, TIER_STARTER).unwrap(), U256::ZERO);
 
-        // Emit event
-        self.vm().log(BillingPayment {
-            app_id: app_id_u256,
-            developer,
-            amount: payment,
-            period_hash: period_u256,
-            timestamp: U256::from(self.__stylus_host.block_timestamp()),
-        });
+        contract.report_usage(APP_ID, 202602, TIER_STARTER_QUOTA + 10_000).unwrap();
+        assert_eq!(
+            contract.report_usage(APP_ID, 202602, TIER_STARTER_QUOTA),
+            Err(ERROR_STALE_USAGE.to_vec())
+        );
 
-        Ok(())
+        let overage = U256::from(10_000u64 * 1_000);
+        let amount_due = U256::from(TIER_STARTER_PRICE) + overage;
+        assert_eq!(contract.get_overage_amount(APP_ID, 202602, TIER_STARTER).unwrap(), overage);
+        assert_eq!(contract.get_period_amount_due(APP_ID, 202602, TIER_STARTER).unwrap(), amount_due);
+
+        vm.set_sender(developer);
+        let invoice_id = contract
+            .issue_invoice(APP_ID, developer, 202602, TIER_STARTER, U256::ZERO, 2_000)
+            .unwrap();
+        let (_, _, _, _, amount, _, _, _, _) = contract.get_invoice(invoice_id).unwrap();
+        assert_eq!(amount, amount_due);
+
+        // Only reporters may report directly
+        assert_eq!(
+            contract.report_usage(APP_ID, 202603, 1),
+            Err(ERROR_UNAUTHORIZED.to_vec())
+        );
     }
 
-    // ============ View Functions ============
+    #[test]
+    fn test_signed_usage_batch_requires_reporter_signature() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let reporter = mock_address(4);
+        let mut contract = setup(&vm, developer);
+        contract.set_reporter(reporter, true).unwrap();
 
-    /// Get app pricing details
-    pub fn get_app_pricing(&self, app_id: u64) -> Result<(
-        u64,      // price_usdc
-        Address,  // developer
-        bool,     // is_subscription
-        u64,      // interval_days
-        bool,     // is_active
-    ), Vec<u8>> {
-        let app_id_u256 = U256::from(app_id);
+        let app_ids = vec![APP_ID, 2];
+        let periods = vec![202602, 202602];
+        let counts = vec![1_200, 3_400];
+        let digest = contract
+            .get_usage_batch_digest(app_ids.clone(), periods.clone(), counts.clone())
+            .unwrap();
+        let (v, r, s) = (27u8, B256::repeat_byte(0x11), B256::repeat_byte(0x22));
 
-        Ok((
-            self.app_prices.get(app_id_u256).to::<u64>(),
-            self.app_developers.get(app_id_u256),
-            self.app_is_subscription.get(app_id_u256),
-            self.app_interval_days.get(app_id_u256).to::<u64>(),
-            self.app_pricing_active.get(app_id_u256),
-        ))
+        // Unrecoverable signature
+        assert_eq!(
+            contract.report_usage_batch(app_ids.clone(), periods.clone(), counts.clone(), v, r, s),
+            Err(ERROR_INVALID_SIGNATURE.to_vec())
+        );
+
+        // ecrecover returns the reporter, relayed by a third party
+        let mut input = digest.to_vec();
+        input.extend_from_slice(&U256::from(v).to_be_bytes::<32>());
+        input.extend_from_slice(r.as_slice());
+        input.extend_from_slice(s.as_slice());
+        vm.mock_static_call(
+            Address::from_slice(&ECRECOVER),
+            input,
+            Ok(reporter.into_word().to_vec()),
+        );
+        vm.set_sender(mock_address(9));
+        contract.report_usage_batch(app_ids, periods, counts, v, r, s).unwrap();
+
+        assert_eq!(contract.get_usage(APP_ID, 202602).unwrap(), 1_200);
+        assert_eq!(contract.get_usage(2, 202602).unwrap(), 3_400);
+        assert_eq!(
+            contract.report_usage_batch(vec![APP_ID], vec![], vec![1], v, r, s),
+            Err(ERROR_INVALID_BATCH.to_vec())
+        );
     }
 
-    /// Check if a user has purchased an app
-    pub fn has_user_purchased(&self, app_id: u64, buyer: Address) -> Result<bool, Vec<u8>> {
-        let app_id_u256 = U256::from(app_id);
-        Ok(self.has_purchased.getter(app_id_u256).get(buyer))
+    fn permit_calldata(vm: &TestVM, owner: Address, value: U256) -> Vec<u8> {
+        permitCall {
+            owner,
+            spender: vm.contract_address(),
+            value,
+            deadline: U256::from(1_000u64),
+            v: 27,
+            r: B256::repeat_byte(0x11),
+            s: B256::repeat_byte(0x22),
+        }.abi_encode()
     }
 
-    /// Get billing payment for a period
-    pub fn get_billing_payment(&self, app_id: u64, period_hash: u64) -> Result<u64, Vec<u8>> {
-        let app_id_u256 = U256::from(app_id);
-        let period_u256 = U256::from(period_hash);
-        Ok(self.billing_payments.getter(app_id_u256).get(period_u256).to::<u64>())
+    #[test]
+    fn test_purchase_with_permit() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let mut contract = setup(&vm, developer);
+        let (v, r, s) = (27u8, B256::repeat_byte(0x11), B256::repeat_byte(0x22));
+
+        mock_usdc(&vm, permit_calldata(&vm, buyer, PRICE), Ok(Vec::new()));
+        vm.set_sender(buyer);
+        contract.purchase_app_with_permit(APP_ID, PRICE, U256::from(1_000u64), v, r, s).unwrap();
+
+        assert!(contract.has_user_purchased(APP_ID, buyer).unwrap());
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), DEVELOPER_SHARE);
     }
 
-    /// Get total platform revenue (analytics)
-    pub fn get_total_platform_revenue(&self) -> Result<u64, Vec<u8>> {
-        Ok(self.total_platform_revenue.get().to::<u64>())
+    #[test]
+    fn test_permit_purchase_after_price_drop() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let mut contract = setup(&vm, developer);
+        let (v, r, s) = (27u8, B256::repeat_byte(0x11), B256::repeat_byte(0x22));
+        let new_price = PRICE / U256::from(2);
+
+        // The buyer signed for the quoted price; the price drops before execution
+        contract.update_app_price(APP_ID, new_price).unwrap();
+        mock_usdc(&vm, permit_calldata(&vm, buyer, PRICE), Ok(Vec::new()));
+        mock_usdc(&vm, permit_calldata(&vm, buyer, new_price), Err(b"invalid signature".to_vec()));
+        let allowance_calldata = allowanceCall { owner: buyer, spender: vm.contract_address() }.abi_encode();
+        vm.mock_static_call(usdc(), allowance_calldata, Ok(U256::ZERO.to_be_bytes::<32>().to_vec()));
+
+        vm.set_sender(buyer);
+        contract.purchase_app_with_permit(APP_ID, PRICE, U256::from(1_000u64), v, r, s).unwrap();
+        assert_eq!(contract.get_purchase_record(APP_ID, buyer).unwrap().1, new_price);
     }
 
-    /// Get total developer payouts (analytics)
-    pub fn get_total_developer_payouts(&self) -> Result<u64, Vec<u8>> {
-        Ok(self.total_developer_payouts.get().to::<u64>())
+    #[test]
+    fn test_failed_permit_falls_back_to_allowance() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let mut contract = setup(&vm, developer);
+        let (v, r, s) = (27u8, B256::repeat_byte(0x11), B256::repeat_byte(0x22));
+        let bill = U256::from(TIER_STARTER_PRICE);
+
+        // Permit already used (e.g., front-run) and no allowance left
+        mock_usdc(&vm, permit_calldata(&vm, buyer, bill), Err(b"used nonce".to_vec()));
+        let allowance_calldata = allowanceCall { owner: buyer, spender: vm.contract_address() }.abi_encode();
+        vm.mock_static_call(usdc(), allowance_calldata.clone(), Ok(U256::ZERO.to_be_bytes::<32>().to_vec()));
+
+        vm.set_sender(buyer);
+        assert_eq!(
+            contract.pay_bill_with_permit(APP_ID, 202602, bill, U256::from(1_000u64), v, r, s),
+            Err(ERROR_PERMIT_FAILED.to_vec())
+        );
+
+        // The front-running permit left a sufficient allowance
+        vm.mock_static_call(usdc(), allowance_calldata, Ok(bill.to_be_bytes::<32>().to_vec()));
+        contract.pay_bill_with_permit(APP_ID, 202602, bill, U256::from(1_000u64), v, r, s).unwrap();
+        assert_eq!(contract.get_billing_payment(APP_ID, 202602).unwrap(), bill);
     }
 
-    /// Get the treasury address
-    pub fn get_treasury(&self) -> Result<Address, Vec<u8>> {
-        Ok(Address::from_slice(&TREASURY))
+    #[test]
+    fn test_purchase_by_sig_relayed() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let relayer = mock_address(9);
+        let mut contract = setup(&vm, developer);
+        let deadline = U256::from(1_000u64);
+        let (v, r, s) = (27u8, B256::repeat_byte(0x11), B256::repeat_byte(0x22));
+
+        let digest = contract.get_purchase_app_digest(APP_ID, buyer, PRICE, deadline).unwrap();
+        let mut input = digest.to_vec();
+        input.extend_from_slice(&U256::from(v).to_be_bytes::<32>());
+        input.extend_from_slice(r.as_slice());
+        input.extend_from_slice(s.as_slice());
+        // Relayer submits; the buyer is charged and receives access
+        mock_usdc(&vm, pull_calldata(&vm, buyer), Ok(Vec::new()));
+        vm.mock_static_call(Address::from_slice(&ECRECOVER), input, Ok(buyer.into_word().to_vec()));
+        vm.set_sender(relayer);
+        contract.purchase_app_by_sig(APP_ID, buyer, PRICE, deadline, v, r, s).unwrap();
+
+        assert!(contract.has_user_purchased(APP_ID, buyer).unwrap());
+        assert!(!contract.has_user_purchased(APP_ID, relayer).unwrap());
+        assert_eq!(contract.get_purchase_record(APP_ID, buyer).unwrap().1, PRICE);
+        assert_eq!(contract.get_nonce(buyer).unwrap(), U256::from(1));
+
+        // The consumed nonce changes the digest, so the signature cannot be replayed
+        assert_ne!(contract.get_purchase_app_digest(APP_ID, buyer, PRICE, deadline).unwrap(), digest);
+
+        vm.set_block_timestamp(2_000);
+        assert_eq!(
+            contract.purchase_app_by_sig(APP_ID, buyer, PRICE, deadline, v, r, s),
+            Err(ERROR_SIGNATURE_EXPIRED.to_vec())
+        );
     }
 
-    /// Get the USDC token address (Arbitrum One)
-    pub fn get_usdc_address(&self) -> Result<Address, Vec<u8>> {
-        Ok(Address::from_slice(&USDC_ADDRESS))
+    #[test]
+    fn test_purchase_by_sig_rejects_price_raised_after_signing() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let relayer = mock_address(9);
+        let mut contract = setup(&vm, developer);
+        let deadline = U256::from(1_000u64);
+        let (v, r, s) = (27u8, B256::repeat_byte(0x11), B256::repeat_byte(0x22));
+
+        let digest = contract.get_purchase_app_digest(APP_ID, buyer, PRICE, deadline).unwrap();
+        let mut input = digest.to_vec();
+        input.extend_from_slice(&U256::from(v).to_be_bytes::<32>());
+        input.extend_from_slice(r.as_slice());
+        input.extend_from_slice(s.as_slice());
+        vm.mock_static_call(Address::from_slice(&ECRECOVER), input, Ok(buyer.into_word().to_vec()));
+
+        // The developer raises the price before relaying the buyer's request
+        vm.set_sender(developer);
+        contract.update_app_price(APP_ID, PRICE * U256::from(2)).unwrap();
+        vm.set_sender(relayer);
+        assert_eq!(
+            contract.purchase_app_by_sig(APP_ID, buyer, PRICE, deadline, v, r, s),
+            Err(ERROR_PRICE_ABOVE_MAX.to_vec())
+        );
+
+        // Raising max_price changes the signed digest, which recovers someone else
+        let digest = contract.get_purchase_app_digest(APP_ID, buyer, PRICE * U256::from(2), deadline).unwrap();
+        let mut input = digest.to_vec();
+        input.extend_from_slice(&U256::from(v).to_be_bytes::<32>());
+        input.extend_from_slice(r.as_slice());
+        input.extend_from_slice(s.as_slice());
+        vm.mock_static_call(Address::from_slice(&ECRECOVER), input, Ok(relayer.into_word().to_vec()));
+        assert_eq!(
+            contract.purchase_app_by_sig(APP_ID, buyer, PRICE * U256::from(2), deadline, v, r, s),
+            Err(ERROR_INVALID_SIGNATURE.to_vec())
+        );
+        assert!(!contract.has_user_purchased(APP_ID, buyer).unwrap());
     }
 
-    /// Get contract owner
-    pub fn get_owner(&self) -> Result<Address, Vec<u8>> {
-        Ok(self.owner.get())
+    #[test]
+    fn test_purchase_with_percent_coupon() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let mut contract = setup(&vm, developer);
+        let code_hash = keccak(b"LAUNCH20");
+
+        contract.set_coupon(APP_ID, code_hash, 2_000, U256::ZERO, 1, 0, false).unwrap();
+
+        vm.set_sender(buyer);
+        contract.purchase_app_with_coupon(APP_ID, "LAUNCH20".to_string(), PRICE).unwrap();
+
+        // 20% off, then the 90/10 split on the discounted price
+        let price = U256::from(79_200_000u64);
+        assert_eq!(contract.get_purchase_record(APP_ID, buyer).unwrap().1, price);
+        assert_eq!(contract.get_balance(treasury(), usdc()).unwrap(), U256::from(7_920_000u64));
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), U256::from(71_280_000u64));
+        assert_eq!(contract.get_coupon(APP_ID, code_hash).unwrap().3, 1);
+
+        // Single-use coupon is exhausted
+        vm.set_sender(mock_address(3));
+        assert_eq!(
+            contract.purchase_app_with_coupon(APP_ID, "LAUNCH20".to_string(), PRICE),
+            Err(ERROR_COUPON_EXHAUSTED.to_vec())
+        );
     }
 
-    // ============ Admin Functions ============
+    #[test]
+    fn test_coupon_allowlist_and_expiry() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let mut contract = setup(&vm, developer);
+        let code_hash = keccak(b"FRIENDS");
 
-    /// Transfer ownership (owner only)
-    pub fn transfer_ownership(&mut self, new_owner: Address) -> Result<(), Vec<u8>> {
-        if self.__stylus_host.msg_sender() != self.owner.get() {
-            return Err(ERROR_UNAUTHORIZED.to_vec());
-        }
-        if new_owner == Address::ZERO {
-            return Err(ERROR_UNAUTHORIZED.to_vec());
-        }
+        contract.set_coupon(APP_ID, code_hash, 0, U256::from(10_000_000u64), 0, 500, true).unwrap();
+        assert_eq!(
+            contract.set_coupon(APP_ID, code_hash, 1_000, U256::from(1u64), 0, 0, false),
+            Err(ERROR_INVALID_COUPON.to_vec())
+        );
 
-        self.owner.set(new_owner);
-        Ok(())
+        assert_eq!(
+            contract.get_coupon_discount(APP_ID, "FRIENDS".to_string(), buyer),
+            Err(ERROR_COUPON_NOT_ALLOWED.to_vec())
+        );
+        contract.set_coupon_allowlist(APP_ID, code_hash, vec![buyer], true).unwrap();
+        assert_eq!(
+            contract.get_coupon_discount(APP_ID, "FRIENDS".to_string(), buyer).unwrap(),
+            U256::from(10_000_000u64)
+        );
+
+        vm.set_block_timestamp(501);
+        vm.set_sender(buyer);
+        assert_eq!(
+            contract.purchase_app_with_coupon(APP_ID, "FRIENDS".to_string(), PRICE),
+            Err(ERROR_COUPON_EXPIRED.to_vec())
+        );
+        assert_eq!(
+            contract.purchase_app_with_coupon(APP_ID, "UNKNOWN".to_string(), PRICE),
+            Err(ERROR_INVALID_COUPON.to_vec())
+        );
     }
 
-    // ============ Internal Helpers ============
+    #[test]
+    fn test_revenue_split_requires_owner_confirmation() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let cofounder = mock_address(5);
+        let owner = mock_address(6);
+        let mut contract = VarityPayments::from(&vm);
+        vm.set_sender(owner);
+        contract.initialize().unwrap();
+        vm.set_sender(developer);
+        contract.set_app_price(APP_ID, PRICE, false, 0).unwrap();
 
-    /// Transfer ERC-20 USDC from one address to another via transferFrom
-    /// Requires the `from` address to have approved this contract
-    fn usdc_transfer_from(&self, from: Address, to: Address, amount: U256) -> Result<(), Vec<u8>> {
-        let usdc_addr = Address::from_slice(&USDC_ADDRESS);
+        assert_eq!(
+            contract.propose_payees(APP_ID, vec![developer, cofounder], vec![7_000, 2_000]),
+            Err(ERROR_INVALID_SPLIT.to_vec())
+        );
+        contract.propose_payees(APP_ID, vec![developer, cofounder], vec![7_000, 3_000]).unwrap();
+        assert_eq!(contract.confirm_payees(APP_ID), Err(ERROR_UNAUTHORIZED.to_vec()));
 
-        // Encode transferFrom(from, to, amount)
-        let calldata = transferFromCall {
-            from,
-            to,
-            amount,
-        }.abi_encode();
+        // Not yet confirmed: all to the developer
+        purchase(&vm, &mut contract, mock_address(2));
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), DEVELOPER_SHARE);
 
-        // Call USDC contract
-        unsafe {
-            RawCall::new(&self.__stylus_host)
-                .call(usdc_addr, &calldata)
-                .map_err(|_| ERROR_TRANSFER_FAILED.to_vec())?;
-        }
+        vm.set_sender(owner);
+        contract.confirm_payees(APP_ID).unwrap();
+        assert_eq!(contract.get_payees(APP_ID).unwrap(), (vec![developer, cofounder], vec![7_000, 3_000]));
 
-        Ok(())
+        let buyer = mock_address(3);
+        purchase(&vm, &mut contract, buyer);
+        let cofounder_share = U256::from(26_730_000u64);
+        assert_eq!(contract.get_balance(cofounder, usdc()).unwrap(), cofounder_share);
+        assert_eq!(contract.get_payee_earnings(APP_ID, cofounder).unwrap(), cofounder_share);
+        assert_eq!(
+            contract.get_balance(developer, usdc()).unwrap(),
+            DEVELOPER_SHARE + DEVELOPER_SHARE - cofounder_share
+        );
+
+        // Refund claws back from each payee under the purchase's split
+        vm.set_sender(developer);
+        contract.set_refund_window(APP_ID, 7).unwrap();
+        contract.refund_purchase(APP_ID, buyer).unwrap();
+        assert_eq!(contract.get_balance(cofounder, usdc()).unwrap(), U256::ZERO);
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), DEVELOPER_SHARE);
+    }
+
+    #[test]
+    fn test_propose_payees_rejects_wrapping_shares() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let mut contract = setup(&vm, developer);
+        let payees = vec![developer, mock_address(4)];
+
+        // Would wrap to 10,000 if summed unchecked
+        vm.set_sender(developer);
+        assert_eq!(
+            contract.propose_payees(APP_ID, payees.clone(), vec![u64::MAX, 10_001]),
+            Err(ERROR_INVALID_SPLIT.to_vec())
+        );
+        assert_eq!(
+            contract.propose_payees(APP_ID, payees.clone(), vec![10_001, 0]),
+            Err(ERROR_INVALID_SPLIT.to_vec())
+        );
+        contract.propose_payees(APP_ID, payees, vec![4_000, 6_000]).unwrap();
+    }
+
+    #[test]
+    fn test_referral_commission_comes_from_developer_share() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let affiliate = mock_address(5);
+        let mut contract = setup(&vm, developer);
+
+        vm.set_sender(developer);
+        assert_eq!(
+            contract.set_referral_share(APP_ID, MAX_REFERRAL_BPS + 1),
+            Err(ERROR_INVALID_REFERRAL_SHARE.to_vec())
+        );
+        contract.set_referral_share(APP_ID, 1_000).unwrap();
+
+        vm.set_sender(buyer);
+        assert_eq!(
+            contract.purchase_app_with_referrer(APP_ID, affiliate, PRICE),
+            Err(ERROR_INVALID_REFERRER.to_vec())
+        );
+
+        vm.set_sender(affiliate);
+        contract.register_affiliate().unwrap();
+
+        vm.set_sender(buyer);
+        mock_usdc(&vm, pull_calldata(&vm, buyer), Ok(U256::from(1).to_be_bytes::<32>().to_vec()));
+        contract.purchase_app_with_referrer(APP_ID, affiliate, PRICE).unwrap();
+
+        // 10% of the 89.10 USDC developer share
+        let commission = U256::from(8_910_000u64);
+        assert_eq!(contract.get_balance(affiliate, usdc()).unwrap(), commission);
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), DEVELOPER_SHARE - commission);
+        assert_eq!(contract.get_affiliate(affiliate).unwrap(), (true, commission, U256::from(1)));
+
+        // Refund claws the commission back from the affiliate
+        vm.set_sender(developer);
+        contract.set_refund_window(APP_ID, 7).unwrap();
+        contract.refund_purchase(APP_ID, buyer).unwrap();
+        assert_eq!(contract.get_balance(affiliate, usdc()).unwrap(), U256::ZERO);
+        assert_eq!(contract.get_affiliate(affiliate).unwrap(), (true, U256::ZERO, U256::ZERO));
+        assert_eq!(contract.get_balance(buyer, usdc()).unwrap(), PRICE);
+    }
+
+    #[test]
+    fn test_free_trial_grants_access_until_converted() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let user = mock_address(2);
+        let mut contract = setup_subscription(&vm, developer);
+
+        contract.set_app_price(2, PRICE, false, 0).unwrap();
+        assert_eq!(contract.set_trial_days(2, 14), Err(ERROR_NOT_SUBSCRIPTION.to_vec()));
+        contract.set_trial_days(APP_ID, 14).unwrap();
+
+        vm.set_block_timestamp(1_000);
+        vm.set_sender(user);
+        contract.start_trial(APP_ID).unwrap();
+        assert!(contract.has_access(APP_ID, user).unwrap());
+        assert!(!contract.has_user_purchased(APP_ID, user).unwrap());
+        assert_eq!(contract.start_trial(APP_ID), Err(ERROR_TRIAL_USED.to_vec()));
+
+        // Trial lapses after 14 days
+        vm.set_block_timestamp(1_000 + 14 * SECONDS_PER_DAY);
+        assert!(!contract.has_access(APP_ID, user).unwrap());
+
+        mock_usdc(&vm, pull_calldata(&vm, user), Ok(U256::from(1).to_be_bytes::<32>().to_vec()));
+        contract.purchase_app(APP_ID, PRICE).unwrap();
+        assert!(contract.has_access(APP_ID, user).unwrap());
+        assert_eq!(contract.get_trial(APP_ID, user).unwrap(), (1_000 + 14 * SECONDS_PER_DAY, true));
+        assert_eq!(contract.get_trial_stats(APP_ID).unwrap(), (U256::from(1), U256::from(1)));
+    }
+
+    #[test]
+    fn test_license_token_transfer_and_royalty() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let holder = mock_address(5);
+        let mut contract = setup(&vm, developer);
+        purchase(&vm, &mut contract, buyer);
+
+        let id = U256::from(APP_ID);
+        assert_eq!(contract.balance_of(buyer, id).unwrap(), U256::from(1));
+
+        // Soulbound until the developer opts in
+        vm.set_sender(buyer);
+        assert_eq!(
+            contract.safe_transfer_from(buyer, holder, id, U256::from(1), Bytes::new()),
+            Err(ERROR_NOT_TRANSFERABLE.to_vec())
+        );
+
+        vm.set_sender(developer);
+        contract.set_license_terms(APP_ID, true, 500).unwrap();
+
+        vm.set_sender(buyer);
+        contract.safe_transfer_from(buyer, holder, id, U256::from(1), Bytes::new()).unwrap();
+        assert!(!contract.has_user_purchased(APP_ID, buyer).unwrap());
+        assert!(contract.has_user_purchased(APP_ID, holder).unwrap());
+
+        // 5% royalty on a 100 USDC resale, split 90/10
+        let sale_price = U256::from(100_000_000u64);
+        let royalty = U256::from(5_000_000u64);
+        assert_eq!(contract.royalty_info(id, sale_price).unwrap(), (developer, royalty));
+        vm.set_sender(holder);
+        contract.pay_royalty(APP_ID, sale_price).unwrap();
+        assert_eq!(
+            contract.get_balance(developer, usdc()).unwrap(),
+            DEVELOPER_SHARE + U256::from(4_500_000u64)
+        );
+
+        // The original buyer can no longer be refunded
+        vm.set_sender(developer);
+        contract.set_refund_window(APP_ID, 7).unwrap();
+        assert_eq!(contract.refund_purchase(APP_ID, buyer), Err(ERROR_LICENSE_TRANSFERRED.to_vec()));
+    }
+
+    #[test]
+    fn test_ownership_follows_transferred_license() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let holder = mock_address(5);
+        let mut contract = setup_subscription(&vm, developer);
+        contract.set_trial_days(APP_ID, 14).unwrap();
+        contract.set_license_terms(APP_ID, true, 0).unwrap();
+        purchase(&vm, &mut contract, buyer);
+
+        let id = U256::from(APP_ID);
+        vm.set_sender(buyer);
+        contract.safe_transfer_from(buyer, holder, id, U256::from(1), Bytes::new()).unwrap();
+
+        // The recipient owns the app: no trial and no second copy
+        vm.set_sender(holder);
+        assert_eq!(contract.start_trial(APP_ID), Err(ERROR_ALREADY_PURCHASED.to_vec()));
+        assert_eq!(contract.purchase_app(APP_ID, PRICE), Err(ERROR_ALREADY_PURCHASED.to_vec()));
+
+        // The original buyer no longer does, and can buy again
+        purchase(&vm, &mut contract, buyer);
+        assert_eq!(contract.balance_of(buyer, id).unwrap(), U256::from(1));
+        assert_eq!(contract.balance_of(holder, id).unwrap(), U256::from(1));
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), DEVELOPER_SHARE * U256::from(2));
+    }
+
+    #[test]
+    fn test_seat_purchase_with_volume_discount() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let org = mock_address(2);
+        let member = mock_address(5);
+        let mut contract = setup(&vm, developer);
+
+        vm.set_sender(developer);
+        assert_eq!(
+            contract.set_seat_discounts(APP_ID, vec![50, 10], vec![2_000, 1_000]),
+            Err(ERROR_INVALID_DISCOUNTS.to_vec())
+        );
+        contract.set_seat_discounts(APP_ID, vec![10, 50], vec![1_000, 2_000]).unwrap();
+
+        // 10 seats at 10% off: 891 USDC
+        let total = U256::from(891_000_000u64);
+        assert_eq!(contract.get_seat_price(APP_ID, 9).unwrap(), PRICE * U256::from(9));
+        assert_eq!(contract.get_seat_price(APP_ID, 10).unwrap(), total);
+
+        vm.set_sender(org);
+        contract.purchase_seats(APP_ID, 10, total).unwrap();
+        assert_eq!(contract.get_seats(APP_ID, org).unwrap(), (U256::from(10), U256::ZERO, 0));
+        assert_eq!(contract.get_balance(treasury(), usdc()).unwrap(), U256::from(89_100_000u64));
+
+        contract.assign_seat(APP_ID, member).unwrap();
+        assert!(contract.has_seat(APP_ID, member).unwrap());
+        assert!(contract.has_access(APP_ID, member).unwrap());
+        assert_eq!(contract.assign_seat(APP_ID, member), Err(ERROR_SEAT_ASSIGNED.to_vec()));
+
+        // Only the assigning org can revoke
+        vm.set_sender(member);
+        assert_eq!(contract.revoke_seat(APP_ID, member), Err(ERROR_SEAT_NOT_ASSIGNED.to_vec()));
+        vm.set_sender(org);
+        contract.revoke_seat(APP_ID, member).unwrap();
+        assert!(!contract.has_seat(APP_ID, member).unwrap());
+        assert_eq!(contract.get_seats(APP_ID, org).unwrap(), (U256::from(10), U256::ZERO, 0));
+    }
+
+    #[test]
+    fn test_purchase_seats_rejects_total_raised_after_quote() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let org = mock_address(2);
+        let mut contract = setup(&vm, developer);
+        let quoted = contract.get_seat_price(APP_ID, 5).unwrap();
+
+        // A scheduled price change lands between quote and inclusion
+        vm.set_block_timestamp(1_000);
+        vm.set_sender(developer);
+        contract.schedule_price_change(APP_ID, PRICE * U256::from(2), 2_000).unwrap();
+        vm.set_block_timestamp(2_000);
+        vm.set_sender(org);
+        assert_eq!(contract.purchase_seats(APP_ID, 5, quoted), Err(ERROR_PRICE_ABOVE_MAX.to_vec()));
+        assert_eq!(contract.get_seats(APP_ID, org).unwrap(), (U256::ZERO, U256::ZERO, 0));
+
+        // So does a discount change that lowers the quoted tier's discount
+        vm.set_sender(developer);
+        contract.update_app_price(APP_ID, PRICE).unwrap();
+        contract.set_seat_discounts(APP_ID, vec![5], vec![1_000]).unwrap();
+        let discounted = contract.get_seat_price(APP_ID, 5).unwrap();
+        contract.set_seat_discounts(APP_ID, Vec::new(), Vec::new()).unwrap();
+        vm.set_sender(org);
+        assert_eq!(contract.purchase_seats(APP_ID, 5, discounted), Err(ERROR_PRICE_ABOVE_MAX.to_vec()));
+
+        contract.purchase_seats(APP_ID, 5, quoted).unwrap();
+        assert_eq!(contract.get_seats(APP_ID, org).unwrap(), (U256::from(5), U256::ZERO, 0));
+    }
+
+    #[test]
+    fn test_fee_overrides_and_volume_tiers() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let owner = mock_address(6);
+        let mut contract = VarityPayments::from(&vm);
+        vm.set_sender(owner);
+        contract.initialize().unwrap();
+        vm.set_sender(developer);
+        contract.set_app_price(APP_ID, PRICE, false, 0).unwrap();
+
+        // 5% once the developer has earned 50 USDC
+        vm.set_sender(owner);
+        assert_eq!(
+            contract.set_fee_tiers(vec![U256::from(50_000_000u64)], vec![PLATFORM_FEE_BPS + 1]),
+            Err(ERROR_INVALID_FEE.to_vec())
+        );
+        contract.set_fee_tiers(vec![U256::from(50_000_000u64)], vec![500]).unwrap();
+        assert_eq!(contract.get_fee_bps(APP_ID).unwrap(), PLATFORM_FEE_BPS);
+
+        purchase(&vm, &mut contract, mock_address(2));
+        assert_eq!(contract.get_fee_bps(APP_ID).unwrap(), 500);
+
+        // Developer override beats the tier, app override beats both
+        vm.set_sender(owner);
+        contract.set_developer_fee(developer, 300, true).unwrap();
+        assert_eq!(contract.get_fee_bps(APP_ID).unwrap(), 300);
+        contract.set_app_fee(APP_ID, 0, true).unwrap();
+        assert_eq!(contract.get_fee_bps(APP_ID).unwrap(), 0);
+
+        purchase(&vm, &mut contract, mock_address(3));
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), DEVELOPER_SHARE + PRICE);
+        assert_eq!(contract.get_purchase_record(APP_ID, mock_address(3)).unwrap().2, U256::ZERO);
+    }
+
+    #[test]
+    fn test_scheduled_price_change_and_max_price() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let mut contract = setup(&vm, developer);
+        let new_price = PRICE * U256::from(2);
+
+        vm.set_block_timestamp(1_000);
+        vm.set_sender(developer);
+        contract.schedule_price_change(APP_ID, new_price, 2_000).unwrap();
+        assert_eq!(contract.get_pending_price(APP_ID).unwrap(), (new_price, 2_000));
+        assert_eq!(contract.get_app_pricing(APP_ID).unwrap().0, PRICE);
+
+        // Takes effect at 2_000; a buyer quoted the old price is protected
+        vm.set_block_timestamp(2_000);
+        assert_eq!(contract.get_app_pricing(APP_ID).unwrap().0, new_price);
+        vm.set_sender(buyer);
+        assert_eq!(contract.purchase_app(APP_ID, PRICE), Err(ERROR_PRICE_ABOVE_MAX.to_vec()));
+        contract.purchase_app(APP_ID, new_price).unwrap();
+
+        assert_eq!(
+            contract.get_price_history(APP_ID, 0, 10).unwrap(),
+            (vec![PRICE, new_price], vec![0, 2_000])
+        );
+
+        // Subscription increases need a week's notice
+        vm.set_sender(developer);
+        contract.set_app_price(2, PRICE, true, 30).unwrap();
+        assert_eq!(
+            contract.update_app_price(2, new_price),
+            Err(ERROR_NOTICE_TOO_SHORT.to_vec())
+        );
+        contract
+            .schedule_price_change(2, new_price, 2_000 + PRICE_NOTICE_SECONDS)
+            .unwrap();
+        assert_eq!(contract.get_price_history_count(2).unwrap(), 2);
+        contract.cancel_price_change(2).unwrap();
+        assert_eq!(contract.get_price_history_count(2).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_escrowed_share_with_dispute_refund_and_release() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let other = mock_address(3);
+        let mut contract = setup(&vm, developer);
+        contract.set_escrow_hold(APP_ID, 7).unwrap();
+
+        vm.set_block_timestamp(1_000);
+        purchase(&vm, &mut contract, buyer);
+        purchase(&vm, &mut contract, other);
+        let release_at = 8 * SECONDS_PER_DAY;
+        assert_eq!(contract.get_purchase_escrow(APP_ID, buyer).unwrap(), (release_at, false));
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), U256::ZERO);
+        assert_eq!(
+            contract.get_escrow_balances(developer).unwrap(),
+            (DEVELOPER_SHARE * U256::from(2), U256::ZERO, U256::ZERO)
+        );
+
+        // Buyer disputes during the hold; an admin refunds
+        vm.set_block_timestamp(3 * SECONDS_PER_DAY);
+        vm.set_sender(buyer);
+        contract.open_dispute(APP_ID).unwrap();
+        assert_eq!(contract.open_dispute(APP_ID), Err(ERROR_ALREADY_DISPUTED.to_vec()));
+        assert_eq!(
+            contract.get_escrow_balances(developer).unwrap(),
+            (DEVELOPER_SHARE, U256::ZERO, DEVELOPER_SHARE)
+        );
+        assert_eq!(contract.resolve_dispute(APP_ID, buyer, true), Err(ERROR_UNAUTHORIZED.to_vec()));
+        vm.set_sender(developer);
+        contract.resolve_dispute(APP_ID, buyer, true).unwrap();
+        assert!(!contract.has_user_purchased(APP_ID, buyer).unwrap());
+        assert_eq!(contract.get_balance(buyer, usdc()).unwrap(), PRICE);
+        assert_eq!(
+            contract.get_escrow_balances(developer).unwrap(),
+            (DEVELOPER_SHARE, U256::ZERO, U256::ZERO)
+        );
+
+        // After the hold the share unlocks and can be withdrawn
+        vm.set_block_timestamp(release_at);
+        vm.set_sender(other);
+        assert_eq!(contract.open_dispute(APP_ID), Err(ERROR_DISPUTE_WINDOW_CLOSED.to_vec()));
+        assert_eq!(
+            contract.get_escrow_balances(developer).unwrap(),
+            (U256::ZERO, DEVELOPER_SHARE, U256::ZERO)
+        );
+        vm.set_sender(developer);
+        contract.withdraw_all(usdc(), developer).unwrap();
+        assert_eq!(contract.get_escrow_balances(developer).unwrap(), (U256::ZERO, U256::ZERO, U256::ZERO));
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_escrow_holds_seat_and_royalty_proceeds() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let org = mock_address(2);
+        let marketplace = mock_address(3);
+        let mut contract = setup(&vm, developer);
+        contract.set_escrow_hold(APP_ID, 7).unwrap();
+        contract.set_license_terms(APP_ID, true, 1_000).unwrap();
+
+        vm.set_block_timestamp(1_000);
+        vm.set_sender(org);
+        contract.purchase_seats(APP_ID, 2, PRICE * U256::from(2)).unwrap();
+        vm.set_sender(marketplace);
+        contract.pay_royalty(APP_ID, PRICE * U256::from(10)).unwrap();
+
+        // Both developer shares are held; the platform fee is not
+        let held = DEVELOPER_SHARE * U256::from(3);
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), U256::ZERO);
+        assert_eq!(contract.get_escrow_balances(developer).unwrap(), (held, U256::ZERO, U256::ZERO));
+        assert_eq!(contract.get_balance(treasury(), usdc()).unwrap(), PLATFORM_FEE * U256::from(3));
+
+        vm.set_block_timestamp(8 * SECONDS_PER_DAY);
+        assert_eq!(contract.get_escrow_balances(developer).unwrap(), (U256::ZERO, held, U256::ZERO));
+        contract.release_escrow(developer).unwrap();
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), held);
+    }
+
+    #[test]
+    fn test_deactivation_keeps_lifetime_licenses_and_reactivates() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let mut contract = setup(&vm, developer);
+        purchase(&vm, &mut contract, buyer);
+
+        vm.set_sender(developer);
+        contract.deactivate_app_pricing(APP_ID, true).unwrap();
+        assert!(!contract.get_app_pricing(APP_ID).unwrap().4);
+        assert!(contract.has_user_purchased(APP_ID, buyer).unwrap());
+        assert!(contract.has_access(APP_ID, buyer).unwrap());
+        vm.set_sender(buyer);
+        assert_eq!(contract.claim_subscription_refund(APP_ID), Err(ERROR_REFUND_UNAVAILABLE.to_vec()));
+
+        vm.set_sender(developer);
+        contract.reactivate_app_pricing(APP_ID).unwrap();
+        assert_eq!(contract.get_app_pricing(APP_ID).unwrap().0, PRICE);
+        assert!(contract.get_app_pricing(APP_ID).unwrap().4);
+        assert_eq!(contract.reactivate_app_pricing(APP_ID), Err(ERROR_APP_ACTIVE.to_vec()));
+    }
+
+    #[test]
+    fn test_deactivated_subscription_grace_access_and_prorata_refund() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let subscriber = mock_address(2);
+        let other = mock_address(3);
+        let mut contract = setup_subscription(&vm, developer);
+
+        vm.set_block_timestamp(1_000);
+        purchase(&vm, &mut contract, subscriber);
+        purchase(&vm, &mut contract, other);
+
+        // Stopped selling with 20 of 30 days unused
+        vm.set_block_timestamp(1_000 + 10 * SECONDS_PER_DAY);
+        vm.set_sender(developer);
+        contract.deactivate_app_pricing(APP_ID, true).unwrap();
+        let unused = |amount: U256| amount * U256::from(20) / U256::from(30);
+        let refund = unused(PRICE);
+        assert_eq!(contract.get_subscription_refund(APP_ID, subscriber).unwrap(), refund);
+
+        // The unused part of each side of the split is reversed
+        vm.set_sender(subscriber);
+        contract.claim_subscription_refund(APP_ID).unwrap();
+        assert_eq!(contract.get_balance(subscriber, usdc()).unwrap(), refund);
+        assert_eq!(
+            contract.get_balance(developer, usdc()).unwrap(),
+            DEVELOPER_SHARE * U256::from(2) - unused(DEVELOPER_SHARE)
+        );
+        assert_eq!(contract.get_balance(treasury(), usdc()).unwrap(), PLATFORM_FEE * U256::from(2) - unused(PLATFORM_FEE));
+        assert_eq!(contract.get_app_gross_revenue(APP_ID).unwrap(), PRICE * U256::from(2) - refund);
+        assert_eq!(contract.get_total_platform_revenue().unwrap(), PLATFORM_FEE * U256::from(2) - unused(PLATFORM_FEE));
+        assert!(!contract.has_access(APP_ID, subscriber).unwrap());
+        assert_eq!(contract.get_app_lifetime_buyers(APP_ID).unwrap(), U256::from(1));
+
+        // The other subscriber keeps access until their paid period ends
+        assert!(contract.has_access(APP_ID, other).unwrap());
+        vm.set_block_timestamp(1_000 + 30 * SECONDS_PER_DAY);
+        assert!(!contract.has_access(APP_ID, other).unwrap());
+        vm.set_sender(other);
+        assert_eq!(contract.claim_subscription_refund(APP_ID), Err(ERROR_REFUND_UNAVAILABLE.to_vec()));
+    }
+
+    #[test]
+    fn test_subscription_and_seats_expire_at_paid_through() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let subscriber = mock_address(2);
+        let org = mock_address(3);
+        let member = mock_address(4);
+        let mut contract = setup_subscription(&vm, developer);
+        let interval = 30 * SECONDS_PER_DAY;
+
+        vm.set_block_timestamp(1_000);
+        purchase(&vm, &mut contract, subscriber);
+        vm.set_sender(org);
+        contract.purchase_seats(APP_ID, 2, PRICE * U256::from(2)).unwrap();
+        contract.assign_seat(APP_ID, member).unwrap();
+        assert_eq!(contract.get_seats(APP_ID, org).unwrap(), (U256::from(2), U256::from(1), 1_000 + interval));
+
+        // Seats added mid-interval end with the others and are charged pro
+        // rata (max_total bounds the prorated amount)
+        vm.set_block_timestamp(1_000 + 15 * SECONDS_PER_DAY);
+        contract.purchase_seats(APP_ID, 1, PRICE / U256::from(2)).unwrap();
+        assert_eq!(contract.get_app_gross_revenue(APP_ID).unwrap(), PRICE * U256::from(3) + PRICE / U256::from(2));
+
+        // Selling has not stopped, yet access ends with the paid interval
+        vm.set_block_timestamp(1_000 + interval);
+        assert!(!contract.has_access(APP_ID, subscriber).unwrap());
+        assert!(!contract.has_access(APP_ID, member).unwrap());
+        assert!(!contract.has_seat(APP_ID, member).unwrap());
+        assert_eq!(contract.purchase_seats(APP_ID, 1, PRICE), Err(ERROR_SEATS_EXPIRED.to_vec()));
+        assert_eq!(contract.assign_seat(APP_ID, mock_address(7)), Err(ERROR_SEATS_EXPIRED.to_vec()));
+
+        vm.set_sender(subscriber);
+        assert_eq!(contract.renew_subscription(APP_ID, PRICE - U256::from(1)), Err(ERROR_PRICE_ABOVE_MAX.to_vec()));
+        contract.renew_subscription(APP_ID, PRICE).unwrap();
+        assert!(contract.has_access(APP_ID, subscriber).unwrap());
+        vm.set_sender(org);
+        contract.renew_seats(APP_ID, PRICE * U256::from(3)).unwrap();
+        assert!(contract.has_access(APP_ID, member).unwrap());
+        assert!(contract.has_seat(APP_ID, member).unwrap());
+        contract.assign_seat(APP_ID, mock_address(7)).unwrap();
+        assert_eq!(contract.get_seats(APP_ID, org).unwrap().2, 1_000 + 2 * interval);
+
+        // Renewals are paid like purchases
+        assert_eq!(contract.get_app_gross_revenue(APP_ID).unwrap(), PRICE * U256::from(7) + PRICE / U256::from(2));
+        vm.set_block_timestamp(1_000 + 2 * interval);
+        assert!(!contract.has_access(APP_ID, subscriber).unwrap());
+
+        // Only license holders can renew
+        vm.set_sender(member);
+        assert_eq!(contract.renew_subscription(APP_ID, PRICE), Err(ERROR_NOT_PURCHASED.to_vec()));
+    }
+
+    #[test]
+    fn test_owed_subscriber_refunds_cannot_be_withdrawn() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let subscriber = mock_address(2);
+        let mut contract = setup_subscription(&vm, developer);
+
+        vm.set_block_timestamp(1_000);
+        purchase(&vm, &mut contract, subscriber);
+        vm.set_block_timestamp(1_000 + 10 * SECONDS_PER_DAY);
+        vm.set_sender(developer);
+        contract.deactivate_app_pricing(APP_ID, true).unwrap();
+
+        // Neither relisting nor reactivating cancels the refund offer
+        assert_eq!(contract.set_app_price(APP_ID, PRICE, true, 30), Err(ERROR_PRICING_SET.to_vec()));
+        assert_eq!(contract.reactivate_app_pricing(APP_ID), Err(ERROR_REFUNDS_OUTSTANDING.to_vec()));
+        vm.set_sender(subscriber);
+        contract.claim_subscription_refund(APP_ID).unwrap();
+
+        // Reactivation is allowed once the last paid interval has ended
+        vm.set_block_timestamp(1_000 + 30 * SECONDS_PER_DAY);
+        vm.set_sender(developer);
+        contract.reactivate_app_pricing(APP_ID).unwrap();
+        assert!(contract.get_app_pricing(APP_ID).unwrap().4);
+    }
+
+    #[test]
+    fn test_repurchase_after_subscription_refund_starts_clean() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let subscriber = mock_address(2);
+        let affiliate = mock_address(3);
+        let mut contract = setup_subscription(&vm, developer);
+        contract.set_referral_share(APP_ID, 1_000).unwrap();
+        vm.set_sender(affiliate);
+        contract.register_affiliate().unwrap();
+
+        vm.set_block_timestamp(1_000);
+        vm.set_sender(subscriber);
+        contract.purchase_app_with_referrer(APP_ID, affiliate, PRICE).unwrap();
+        let commission = DEVELOPER_SHARE / U256::from(10);
+
+        vm.set_block_timestamp(1_000 + 10 * SECONDS_PER_DAY);
+        vm.set_sender(developer);
+        contract.deactivate_app_pricing(APP_ID, true).unwrap();
+        vm.set_sender(subscriber);
+        contract.claim_subscription_refund(APP_ID).unwrap();
+        assert_eq!(contract.get_purchase_record(APP_ID, subscriber).unwrap(), (0, U256::ZERO, U256::ZERO));
+
+        // The affiliate keeps the commission for the used third of the interval
+        let kept_commission = commission - commission * U256::from(20) / U256::from(30);
+        assert_eq!(contract.get_balance(affiliate, usdc()).unwrap(), kept_commission);
+
+        // Repurchase with no referrer once refunds lapse: the old affiliate is not paid again
+        vm.set_block_timestamp(1_000 + 30 * SECONDS_PER_DAY);
+        vm.set_sender(developer);
+        contract.reactivate_app_pricing(APP_ID).unwrap();
+        let developer_balance = contract.get_balance(developer, usdc()).unwrap();
+        purchase(&vm, &mut contract, subscriber);
+        assert_eq!(contract.get_balance(affiliate, usdc()).unwrap(), kept_commission);
+        assert_eq!(contract.get_balance(developer, usdc()).unwrap(), developer_balance + DEVELOPER_SHARE);
+        assert_eq!(contract.get_purchase_record(APP_ID, subscriber).unwrap(), (1_000 + 30 * SECONDS_PER_DAY, PRICE, PLATFORM_FEE));
+        assert_eq!(contract.get_affiliate(affiliate).unwrap().2, U256::ZERO);
+    }
+
+    #[test]
+    fn test_claim_free_app_records_entitlement() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let user = mock_address(2);
+        let mut contract = setup(&vm, developer);
+
+        vm.set_sender(user);
+        assert_eq!(contract.claim_free_app(APP_ID), Err(ERROR_APP_NOT_FREE.to_vec()));
+
+        vm.set_sender(developer);
+        contract.update_app_price(APP_ID, U256::ZERO).unwrap();
+        assert_eq!(contract.purchase_app(APP_ID, U256::ZERO), Err(ERROR_APP_NOT_FOR_SALE.to_vec()));
+
+        vm.set_block_timestamp(1_000);
+        vm.set_sender(user);
+        contract.claim_free_app(APP_ID).unwrap();
+        assert!(contract.has_user_purchased(APP_ID, user).unwrap());
+        assert!(contract.has_access(APP_ID, user).unwrap());
+        assert_eq!(contract.get_purchase_record(APP_ID, user).unwrap(), (1_000, U256::ZERO, U256::ZERO));
+        assert_eq!(contract.get_app_purchase_count(APP_ID).unwrap(), U256::from(1));
+        assert_eq!(contract.claim_free_app(APP_ID), Err(ERROR_ALREADY_PURCHASED.to_vec()));
+
+        // Subscriptions cannot be free
+        vm.set_sender(developer);
+        assert_eq!(contract.set_app_price(2, U256::ZERO, true, 30), Err(ERROR_INVALID_PRICE.to_vec()));
+    }
+
+    #[test]
+    fn test_user_library_lists_held_licenses() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let mut contract = setup(&vm, developer);
+        contract.set_app_price(2, PRICE, true, 30).unwrap();
+        contract.set_app_price(3, U256::ZERO, false, 0).unwrap();
+        contract.set_refund_window(APP_ID, 14).unwrap();
+
+        vm.set_block_timestamp(1_000);
+        purchase(&vm, &mut contract, buyer);
+        contract.purchase_app(2, PRICE).unwrap();
+        vm.set_block_timestamp(2_000);
+        contract.claim_free_app(3).unwrap();
+
+        assert_eq!(contract.get_library_count(buyer).unwrap(), 3);
+        assert_eq!(
+            contract.get_library(buyer, 0, 10).unwrap(),
+            (vec![1, 2, 3], vec![1_000, 1_000, 2_000], vec![0, 1_000 + 30 * SECONDS_PER_DAY, 0])
+        );
+        assert_eq!(contract.get_library(buyer, 1, 1).unwrap(), (vec![2], vec![1_000], vec![1_000 + 30 * SECONDS_PER_DAY]));
+        assert_eq!(contract.get_library(buyer, 5, 10).unwrap(), (vec![], vec![], vec![]));
+
+        // A refund removes the app; the last entry takes its slot
+        vm.set_sender(developer);
+        contract.refund_purchase(APP_ID, buyer).unwrap();
+        assert_eq!(contract.get_library(buyer, 0, 10).unwrap().0, vec![3, 2]);
+    }
+
+    #[test]
+    fn test_library_reports_expiry_of_transferred_subscription() {
+        let vm = TestVM::default();
+        let developer = mock_address(1);
+        let buyer = mock_address(2);
+        let holder = mock_address(5);
+        let mut contract = setup(&vm, developer);
+        contract.set_app_price(2, PRICE, true, 30).unwrap();
+        contract.set_license_terms(2, true, 0).unwrap();
+
+        vm.set_block_timestamp(1_000);
+        vm.set_sender(buyer);
+        contract.purchase_app(2, PRICE).unwrap();
+        contract.safe_transfer_from(buyer, holder, U256::from(2), U256::from(1), Bytes::new()).unwrap();
+
+        // No purchase time, but the paid interval came with the license
+        let ends_at = 1_000 + 30 * SECONDS_PER_DAY;
+        assert_eq!(contract.get_library(holder, 0, 10).unwrap(), (vec![2], vec![0], vec![ends_at]));
+        assert!(contract.has_access(2, holder).unwrap());
+        vm.set_block_timestamp(ends_at);
+        assert!(!contract.has_access(2, holder).unwrap());
     }
 }
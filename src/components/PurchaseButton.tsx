@@ -125,16 +125,53 @@ export function PurchaseButton({ appId, className = "" }: PurchaseButtonProps):
     );
   }
 
-  // No pricing set — app is free
-  if (!pricing || !pricing.isActive || pricing.priceUsdc === BigInt(0)) {
+  // No pricing set — app is free without an on-chain entitlement
+  if (!pricing || !pricing.isActive) {
     return <></>;
   }
 
+  const isFree = pricing.priceUsdc === BigInt(0);
+
   if (hasPurchased) {
     return (
       <div className={`inline-flex items-center gap-2 rounded-xl bg-green-500/20 px-6 py-3 text-green-400 ${className}`}>
         <Check className="h-5 w-5" />
-        Purchased
+        {isFree ? "In Your Library" : "Purchased"}
+      </div>
+    );
+  }
+
+  // Listed at zero — claim it into the user's library (no payment)
+  if (isFree) {
+    return (
+      <div className={`flex flex-col gap-3 ${className}`}>
+        {error && (
+          <p className="text-sm text-red-400">{error}</p>
+        )}
+
+        <TransactionButton
+          transaction={async () => {
+            const contract = getPaymentsContract();
+            return prepareContractCall({
+              contract,
+              method: PAYMENTS_ABI.find(m => m.name === "claimFreeApp")!,
+              params: [appId],
+            });
+          }}
+          onTransactionConfirmed={() => {
+            setHasPurchased(true);
+            setError(null);
+          }}
+          onError={(err) => {
+            setError("Claim failed. Please try again.");
+            console.error("Free app claim failed:", err);
+          }}
+        >
+          <div className="inline-flex items-center gap-2 rounded-xl bg-brand-500 px-6 py-3 text-base font-semibold text-slate-950 shadow-lg transition-all hover:bg-brand-400 hover:shadow-xl">
+            <ShoppingCart className="h-5 w-5" />
+            Get for Free
+          </div>
+        </TransactionButton>
       </div>
     );
   }
@@ -191,7 +228,8 @@ export function PurchaseButton({ appId, className = "" }: PurchaseButtonProps):
             return prepareContractCall({
               contract,
               method: PAYMENTS_ABI.find(m => m.name === "purchaseApp")!,
-              params: [appId],
+              // Cap at the quoted price so a price change can't overcharge
+              params: [appId, pricing.priceUsdc],
             });
           }}
           onTransactionConfirmed={() => {
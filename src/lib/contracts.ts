@@ -238,7 +238,7 @@ export const PAYMENTS_ABI = [
     stateMutability: "view",
     inputs: [{ name: "appId", type: "uint64" }],
     outputs: [
-      { name: "priceUsdc", type: "uint64" },
+      { name: "priceUsdc", type: "uint256" },
       { name: "developer", type: "address" },
       { name: "isSubscription", type: "bool" },
       { name: "intervalDays", type: "uint64" },
@@ -255,6 +255,28 @@ export const PAYMENTS_ABI = [
     ],
     outputs: [{ name: "", type: "bool" }],
   },
+  {
+    name: "getLibraryCount",
+    type: "function",
+    stateMutability: "view",
+    inputs: [{ name: "user", type: "address" }],
+    outputs: [{ name: "", type: "uint64" }],
+  },
+  {
+    name: "getLibrary",
+    type: "function",
+    stateMutability: "view",
+    inputs: [
+      { name: "user", type: "address" },
+      { name: "offset", type: "uint64" },
+      { name: "limit", type: "uint64" },
+    ],
+    outputs: [
+      { name: "appIds", type: "uint64[]" },
+      { name: "purchasedAt", type: "uint64[]" },
+      { name: "expiresAt", type: "uint64[]" },
+    ],
+  },
   {
     name: "getTreasury",
     type: "function",
@@ -274,7 +296,7 @@ export const PAYMENTS_ABI = [
     type: "function",
     stateMutability: "view",
     inputs: [],
-    outputs: [{ name: "", type: "uint64" }],
+    outputs: [{ name: "", type: "uint256" }],
   },
   {
     name: "getUsdcAddress",
@@ -285,21 +307,57 @@ export const PAYMENTS_ABI = [
   },
   // Write functions (camelCase - Stylus SDK conversion)
   // NOTE: All payment functions are nonpayable — they use ERC-20 USDC transferFrom
-  // Users must approve() USDC spending before calling purchaseApp/payBill
+  // Users must approve() USDC spending before calling purchaseApp/payBill,
+  // or sign an EIP-2612 permit for the *WithPermit variants
   {
     name: "purchaseApp",
     type: "function",
     stateMutability: "nonpayable",
+    inputs: [
+      { name: "appId", type: "uint64" },
+      { name: "maxPrice", type: "uint256" },
+    ],
+    outputs: [],
+  },
+  {
+    name: "claimFreeApp",
+    type: "function",
+    stateMutability: "nonpayable",
     inputs: [{ name: "appId", type: "uint64" }],
     outputs: [],
   },
+  {
+    name: "purchaseAppFor",
+    type: "function",
+    stateMutability: "nonpayable",
+    inputs: [
+      { name: "appId", type: "uint64" },
+      { name: "recipient", type: "address" },
+      { name: "maxPrice", type: "uint256" },
+    ],
+    outputs: [],
+  },
+  {
+    name: "purchaseAppWithPermit",
+    type: "function",
+    stateMutability: "nonpayable",
+    inputs: [
+      { name: "appId", type: "uint64" },
+      { name: "maxPrice", type: "uint256" },
+      { name: "deadline", type: "uint256" },
+      { name: "v", type: "uint8" },
+      { name: "r", type: "bytes32" },
+      { name: "s", type: "bytes32" },
+    ],
+    outputs: [],
+  },
   {
     name: "setAppPrice",
     type: "function",
     stateMutability: "nonpayable",
     inputs: [
       { name: "appId", type: "uint64" },
-      { name: "priceUsdc", type: "uint64" },
+      { name: "priceUsdc", type: "uint256" },
       { name: "isSubscription", type: "bool" },
       { name: "intervalDays", type: "uint64" },
     ],
@@ -312,7 +370,22 @@ export const PAYMENTS_ABI = [
     inputs: [
       { name: "appId", type: "uint64" },
       { name: "periodHash", type: "uint64" },
-      { name: "amount", type: "uint64" },
+      { name: "amount", type: "uint256" },
+    ],
+    outputs: [],
+  },
+  {
+    name: "payBillWithPermit",
+    type: "function",
+    stateMutability: "nonpayable",
+    inputs: [
+      { name: "appId", type: "uint64" },
+      { name: "periodHash", type: "uint64" },
+      { name: "amount", type: "uint256" },
+      { name: "deadline", type: "uint256" },
+      { name: "v", type: "uint8" },
+      { name: "r", type: "bytes32" },
+      { name: "s", type: "bytes32" },
     ],
     outputs: [],
   },
@@ -321,7 +394,7 @@ export const PAYMENTS_ABI = [
     type: "function",
     stateMutability: "view",
     inputs: [],
-    outputs: [{ name: "", type: "uint64" }],
+    outputs: [{ name: "", type: "uint256" }],
   },
   {
     name: "updateAppPrice",
@@ -329,7 +402,7 @@ export const PAYMENTS_ABI = [
     stateMutability: "nonpayable",
     inputs: [
       { name: "appId", type: "uint64" },
-      { name: "newPriceUsdc", type: "uint64" },
+      { name: "newPriceUsdc", type: "uint256" },
     ],
     outputs: [],
   },
@@ -337,7 +410,10 @@ export const PAYMENTS_ABI = [
     name: "deactivateAppPricing",
     type: "function",
     stateMutability: "nonpayable",
-    inputs: [{ name: "appId", type: "uint64" }],
+    inputs: [
+      { name: "appId", type: "uint64" },
+      { name: "refundSubscribers", type: "bool" },
+    ],
     outputs: [],
   },
   {
@@ -353,11 +429,13 @@ export const PAYMENTS_ABI = [
     type: "event",
     inputs: [
       { name: "app_id", type: "uint256", indexed: true },
-      { name: "buyer", type: "address", indexed: true },
+      { name: "recipient", type: "address", indexed: true },
       { name: "developer", type: "address", indexed: true },
+      { name: "payer", type: "address", indexed: false },
       { name: "total_amount", type: "uint256", indexed: false },
       { name: "developer_share", type: "uint256", indexed: false },
       { name: "platform_fee", type: "uint256", indexed: false },
+      { name: "fee_bps", type: "uint256", indexed: false },
       { name: "timestamp", type: "uint256", indexed: false },
     ],
   },